@@ -0,0 +1,330 @@
+# English, the reference locale every other locale is checked against
+
+## Help
+
+help-title = Commands
+help-description =
+    System
+    - **Help** Show this message.
+    - **Ping** Pong!
+    - **Settings** Change how the bot behaves in this server.
+
+    Moderation
+    - **Stunlock** Manually stunlock a user, optionally for a set duration and with a reason.
+    - **Streak** Modify a user's streak.
+    - **History** Page through a user's past violations.
+    - **Backfill** Record the existing history of a channel, or cancel doing so.
+
+    General
+    - **Check** See whether a message would be original in a channel, without sending it. Also works in DMs.
+    - **Stats** Show what the bot has been up to in this server.
+    - **Leaderboard** Show the most original users in this server.
+    - **Profile** Show a user's originality, streak and stunlocks.
+    - **DMs** Turn the bot's DMs to you on or off, in every server.
+
+    Most commands are also available as slash commands.
+
+## Settings
+
+settings-title = Settings
+settings-description =
+    - **Prefix** Change the command prefix.
+    - **Language** Change the language the bot speaks in this server.
+    - **Whitelist** Add or remove channels and categories to the channel list.
+    - **Mode** Toggle whether the channel list is a whitelist or a blacklist.
+    - **Global** Toggle use of the cross-server dataset.
+    - **Log** Set the channel moderation events are logged in, or disable logging.
+    - **LogContent** Toggle quoting violating messages in the log channel.
+    - **ExemptUser** Add or remove users from enforcement exemptions.
+    - **ExemptRole** Add or remove roles from enforcement exemptions.
+    - **RecordExempt** Toggle recording messages from exempt users.
+    - **Warnings** Set how many violations only get a warning before stunlocks begin.
+    - **Markdown** Toggle `code` blocks being hashed verbatim, `spoilers` markers being stripped, or `urls` being hashed apart from the text.
+    - **Links** Toggle catching reposted links regardless of the text around them.
+    - **Emoji** Toggle checking messages with only custom emoji or stickers.
+    - **Replies** Toggle hashing replies together with the message they reply to.
+    - **Edits** Set how edits are treated: `check`, `ignore`, `grace [window]` or `replace`.
+    - **Reactions** Set how reactions are checked: `off`, `message` or `channel [window]`.
+    - **Retract** Set how long authors have to delete a message to remove it from the dataset, or `off`.
+    - **DMs** Toggle the bot DMing users, they're mentioned in the channel instead.
+    - **Notices** Set where stunlock and warning notices go: `channel [lifetime|off]`, `log` or `dm`.
+    - **Template** Customize the `stunlock` and `unmute` embeds: `title`, `description`, `color`, `show` or `reset`.
+
+setting-current-prefix = The command prefix is: { $prefix }
+setting-changed-prefix = Changed the command prefix to: { $prefix }
+setting-added-channel =
+    Added `{ $channel }` to the channel list
+    Use the backfill command to record its existing messages
+setting-removed-channel = Removed `{ $channel }` from the channel list
+setting-no-channel = Specified argument was not a channel.
+setting-malformed = Specified argument was malformed.
+setting-enabled-global = This guild will now use the global dataset.
+setting-disabled-global = This guild will no longer use the global dataset
+setting-set-log-channel = Moderation events will now be logged in { $channel }
+setting-disabled-log-channel = Moderation events will no longer be logged.
+setting-enabled-log-content = Violating messages will now be quoted in the log channel.
+setting-disabled-log-content = Violating messages will no longer be quoted in the log channel.
+setting-added-exemption = { $target } is now exempt from enforcement
+setting-removed-exemption = { $target } is no longer exempt from enforcement
+setting-no-user = Specified argument was not a user.
+setting-no-role = Specified argument was not a role.
+setting-enabled-record-exempt = Messages from exempt users will now be recorded.
+setting-disabled-record-exempt = Messages from exempt users will no longer be recorded.
+setting-current-warnings = Users get `{ $warnings }` warnings before being stunlocked
+setting-changed-warnings = Users will now get `{ $warnings }` warnings before being stunlocked
+setting-whitelist-mode = R9k is now only enforced in the listed channels and categories.
+setting-blacklist-mode = R9k is now enforced in every channel except the listed channels and categories.
+setting-changed-retract = Messages deleted by their author within `{ $window }` no longer count towards the dataset
+setting-disabled-retract = Deleted messages will stay in the dataset forever.
+setting-current-edit-policy = Edit policy: `{ $policy }`, { $explanation }
+setting-changed-edit-policy = Changed the edit policy to `{ $policy }`, { $explanation }
+setting-edit-check = edits are checked like new messages
+setting-edit-ignore = edits are never checked
+setting-edit-grace = edits are only checked within `{ $window }` of sending
+setting-edit-replace = edits replace the original message in the dataset
+setting-current-reaction-mode = Reaction mode: `{ $mode }`, { $explanation }
+setting-changed-reaction-mode = Changed the reaction mode to `{ $mode }`, { $explanation }
+setting-reaction-off = reactions are never checked
+setting-reaction-message = the same emoji can't be added to a message twice
+setting-reaction-channel = an emoji can't be reused in a channel within `{ $window }`
+setting-enabled-reply-context = Replies will now only be compared against replies to the same message.
+setting-disabled-reply-context = Replies will now be compared regardless of what they reply to.
+setting-current-markdown =
+    Verbatim code: `{ $code }`
+    Strip spoiler markers: `{ $spoilers }`
+    Separate URLs: `{ $urls }`
+setting-changed-markdown = Turned { $option } { $state }
+markdown-option-code = verbatim code
+markdown-option-spoilers = stripping spoiler markers
+markdown-option-urls = separate URLs
+state-on = on
+state-off = off
+setting-enabled-links = Reposted links will now be caught regardless of the text around them.
+setting-disabled-links = Links will now only count as part of the text around them.
+setting-enabled-emoji = Messages with only custom emoji or stickers will now be checked by which ones they contain.
+setting-disabled-emoji = Messages with only custom emoji or stickers will no longer be told apart.
+setting-current-language = This server's language is `{ $language }`, available languages: { $available }
+setting-changed-language = This server's language is now `{ $language }`
+setting-unknown-language = That language isn't available, available languages: { $available }
+setting-enabled-dms = Users will now be sent DMs, unless they opted out.
+setting-disabled-dms = Users will no longer be sent DMs, they'll be mentioned in the channel instead.
+setting-current-notices = Notices: `{ $mode }`, { $explanation }
+setting-changed-notices = Changed notices to `{ $mode }`, { $explanation }
+setting-notice-channel = notices are posted in the channel and deleted after `{ $lifetime }`
+setting-notice-kept = notices are posted in the channel and kept
+setting-notice-log = notices are only sent to the log channel
+setting-notice-dm = notices are sent to the offending user by DM
+setting-no-log-channel = Set a log channel before sending notices to it.
+setting-current-template =
+    **Embed**: `{ $embed }`
+    **Shown**: `{ $shown }`
+    **Color**: { $color }
+    **Title**: { $title }
+    **Description**: { $description }
+    **Placeholders**: { $placeholders }
+setting-changed-template = Changed the `{ $field }` of the { $embed } embed
+setting-reset-template = The { $embed } embed is back to its default
+setting-unknown-template = Specify which embed to customize: { $available }
+template-default = default
+template-too-long = Templates can be at most `{ $limit }` characters long.
+template-unclosed = A `{` is never closed, use `{{` for a literal brace.
+template-unopened = A `}` is never opened, use `}}` for a literal brace.
+template-unknown-placeholder = { $placeholder } isn't a placeholder, available placeholders: { $available }
+
+whitelist-empty-blacklist =
+    No channels have been blacklisted, r9k is enforced everywhere.
+    Do /settings whitelist #channel
+whitelist-empty =
+    No channels have been whitelisted.
+    Do /settings whitelist #channel
+whitelist-blacklist-header = Blacklist mode, r9k is enforced everywhere except:
+whitelist-header = Currently whitelisted channels:
+exempt-empty =
+    No users or roles are exempt.
+    Do /settings exemptuser @user or /settings exemptrole @role
+exempt-header = Currently exempt users and roles:
+
+## Stunlocks
+
+stunlock-title = Stunlocked
+stunlock-automatic =
+    { $user } **was stunlocked for**: `{ $duration }`
+    **Current streak**: `{ $streak }`
+stunlock-manual =
+    { $user } **was stunlocked for**: `{ $duration }`
+    **Current streak**: `{ $streak }`
+    **Moderator**: { $moderator }
+    **Reason**: { $reason }
+stunlock-reaction =
+    { $user } **was stunlocked for**: `{ $duration }`
+    **Reason**: Reacting with { $emoji } after someone else already did
+stunlock-why = [Why did I get stunlocked?](https://github.com/DontStarve72/Ninethousand-Eighty-Four#why-was-i-muted)
+warning-title = Warning
+warning-description =
+    { $user } **your message was unoriginal and has been removed.**
+    **Warnings left before stunlocks**: `{ $remaining }`
+warning-why = [Why was my message removed?](https://github.com/DontStarve72/Ninethousand-Eighty-Four#why-was-i-muted)
+stunlock-dm-title-guild = You were stunlocked in { $guild }
+stunlock-dm-title = You were stunlocked
+stunlock-dm-description =
+    **Duration**: `{ $duration }`
+    **Moderator**: { $moderator }
+    **Reason**: { $reason }
+no-reason = No reason provided.
+reason-automatic = Sending an unoriginal message
+reason-reaction = Reacting with { $emoji } after someone else already did
+unmute-guild = **Your stunlock in** `{ $guild }` **has ended**.
+unmute = Your stunlock has ended.
+dms-enabled = The bot will DM you again.
+dms-disabled = The bot will no longer DM you, you'll be mentioned in the channel instead.
+
+## Statistics
+
+stats-title = Statistics
+stats-counters =
+    **Checked**: `{ $checked }`
+    **Recorded**: `{ $recorded }`
+    **Violations**: `{ $violations }`
+    **Mutes**: `{ $mutes }`
+stats-status = Status
+stats-muted = **Currently muted**: `{ $muted }`
+stats-longest-streak = **Longest active streak**: `{ $streak }` by { $user }
+stats-no-streak = **Longest active streak**: `0`
+stats-dm-failures = **Undeliverable DMs**: `{ $failures }`
+stats-top-offenders = Top offenders
+leaderboard-title = Leaderboard
+leaderboard-empty = No one has sent a message in a whitelisted channel yet.
+leaderboard-entry = { $rank }. { $user } `{ $original }` original, `{ $violations }` violations (`{ $ratio }%`)
+profile-title = Profile
+profile-description =
+    { $user }
+    **Original messages**: `{ $original }`
+    **Violations**: `{ $violations }`
+    **Originality**: `{ $ratio }%`
+    **Current streak**: `{ $streak }`
+    **Stunlocks**: `{ $mutes }`
+profile-decay = **Streak decays in**: `{ $duration }`
+
+## Backfill
+
+backfill-title = Backfill
+backfill-running =
+    Recording the history of { $channel }...
+    **Messages processed**: `{ $count }`
+backfill-done =
+    Finished recording the history of { $channel }.
+    **Messages processed**: `{ $count }`
+backfill-cancelled =
+    Cancelled recording the history of { $channel }, run the command again to resume.
+    **Messages processed**: `{ $count }`
+backfill-failed =
+    Failed to fetch the history of { $channel }, run the command again to resume.
+    **Messages processed**: `{ $count }`
+backfill-already-running = That channel is already being backfilled.
+backfill-not-running = That channel isn't being backfilled.
+backfill-cancelling = Cancelling the backfill, progress has been saved.
+
+## Check
+
+check-original = That message would be **original**.
+check-unoriginal = That message would be **unoriginal**.
+check-not-enforced = That channel isn't whitelisted, so any message is allowed there.
+
+## Moderation
+
+manual-mute = Manually stunlocked { $user }.
+manual-mute-duration = Manually stunlocked { $user } for `{ $duration }`.
+manual-mute-reason = Reason
+manual-streak = Set the streak of the user { $user } to `{ $streak }`
+history-title = History
+history-empty = { $user } has no recorded violations.
+history-header = Violation history of { $user }:
+history-entry = <t:{ $time }:f> in { $channel }
+history-kind = **{ $kind }**, streak `{ $streak }`
+history-kind-duration = **{ $kind }** for `{ $duration }`, streak `{ $streak }`
+history-moderator = by { $moderator }
+history-reason = **Reason**: { $reason }
+history-hash = **Hash**: `{ $hash }`
+history-page = Page { $page } of { $pages }
+history-kind-automatic = automatic
+history-kind-manual = manual
+history-kind-pardon = pardon
+history-kind-warning = warning
+history-kind-reaction = reaction
+
+## Log
+
+log-automatic-title = Automatic stunlock
+log-manual-title = Manual stunlock
+log-reaction-title = Reaction stunlock
+log-warning-title = Warning
+log-pardon-title = Pardon
+log-streak-title = Streak changed
+log-setting-title = Settings changed
+log-whitelist-title = Whitelist changed
+log-mute =
+    { $user } was stunlocked in { $channel } for `{ $duration }`
+    **Streak**: `{ $streak }`
+log-hash = **Hash**: `{ $hash }`
+log-content =
+    **Content**:
+    { $content }
+log-no-content = *No text content*
+log-moderator =
+    **Moderator**: { $moderator }
+    **Reason**: { $reason }
+log-reaction = **Reaction**: { $emoji }
+log-warning =
+    { $user } was warned in { $channel }
+    **Warnings left**: `{ $remaining }`
+log-streak = { $moderator } changed the streak of { $user } from `{ $old }` to `{ $new }`
+log-setting = { $moderator }: { $change }
+log-channel-added = { $moderator } added { $channel } to the channel list
+log-channel-removed = { $moderator } removed { $channel } from the channel list
+log-change-prefix = Changed the command prefix to `{ $prefix }`
+log-change-global-enabled = Enabled the global dataset
+log-change-global-disabled = Disabled the global dataset
+log-change-blacklist = Switched to blacklist mode
+log-change-whitelist = Switched to whitelist mode
+log-change-warnings = Set the amount of warnings before stunlocks to `{ $warnings }`
+log-change-retract = Set the retract window to `{ $window }`
+log-change-retract-off = Turned off retracting deleted messages
+log-change-edit-policy = Set the edit policy to `{ $policy }`
+log-change-dms-enabled = Enabled direct messages
+log-change-dms-disabled = Disabled direct messages
+log-change-notice-mode = Set the notice mode to `{ $mode }`
+log-change-language = Set the language to `{ $language }`
+log-change-reaction-mode = Set the reaction mode to `{ $mode }`
+log-change-log-channel = Set the log channel to { $channel }
+log-change-log-channel-off = Disabled the log channel
+log-change-log-content-enabled = Enabled quoting violating messages in the log
+log-change-log-content-disabled = Disabled quoting violating messages in the log
+log-change-reply-context-enabled = Enabled hashing replies together with the message they reply to
+log-change-reply-context-disabled = Disabled hashing replies together with the message they reply to
+log-change-markdown = Turned { $option } { $state }
+log-change-links-enabled = Enabled catching reposted links
+log-change-links-disabled = Disabled catching reposted links
+log-change-emoji-enabled = Enabled checking emoji and sticker only messages
+log-change-emoji-disabled = Disabled checking emoji and sticker only messages
+log-change-template-reset = Reset the `{ $embed }` embed
+log-change-template = Changed the `{ $field }` of the `{ $embed }` embed
+log-change-exemption-added = Added the exemption of { $target }
+log-change-exemption-removed = Removed the exemption of { $target }
+log-change-record-exempt-enabled = Enabled recording messages from exempt users
+log-change-record-exempt-disabled = Disabled recording messages from exempt users
+
+## Errors
+
+error-no-channel = You must specify a channel in a server you're in.
+error-no-message = You must specify the message to check.
+error-no-user = You must specify a user. See `{ $prefix }help` for examples.
+error-no-int = You must specify a number. See `{ $prefix }help` for examples.
+error-streak-size = Specified streak must be `at least 0` or `below 16`.
+error-rate-limited = You're doing that too often, try again in `{ $duration }`.
+error-unknown-command = Unknown command.
+error-guild-only = This command can only be used in servers.
+error-no-permission = You don't have permission to use this command.
+error-specify-user = You must specify a user.
+error-malformed-duration = Specified duration was malformed, try something like `30m` or `1h30m`.
+error-specify-setting = You must specify a setting.
+error-unknown-setting = Unknown setting.
@@ -0,0 +1,330 @@
+# Spanish
+
+## Help
+
+help-title = Comandos
+help-description =
+    Sistema
+    - **Help** Muestra este mensaje.
+    - **Ping** ¡Pong!
+    - **Settings** Cambia cómo se comporta el bot en este servidor.
+
+    Moderación
+    - **Stunlock** Silencia a un usuario manualmente, opcionalmente durante un tiempo fijo y con un motivo.
+    - **Streak** Modifica la racha de un usuario.
+    - **History** Consulta las infracciones pasadas de un usuario.
+    - **Backfill** Registra el historial existente de un canal, o cancela el registro.
+
+    General
+    - **Check** Comprueba si un mensaje sería original en un canal, sin enviarlo. También funciona por mensaje directo.
+    - **Stats** Muestra lo que ha hecho el bot en este servidor.
+    - **Leaderboard** Muestra a los usuarios más originales de este servidor.
+    - **Profile** Muestra la originalidad, la racha y los silencios de un usuario.
+    - **DMs** Activa o desactiva los mensajes directos del bot, en todos los servidores.
+
+    La mayoría de los comandos también están disponibles como comandos de barra.
+
+## Settings
+
+settings-title = Ajustes
+settings-description =
+    - **Prefix** Cambia el prefijo de los comandos.
+    - **Language** Cambia el idioma que usa el bot en este servidor.
+    - **Whitelist** Añade o quita canales y categorías de la lista de canales.
+    - **Mode** Alterna entre usar la lista de canales como lista blanca o lista negra.
+    - **Global** Alterna el uso del conjunto de datos compartido entre servidores.
+    - **Log** Establece el canal donde se registran los eventos de moderación, o desactiva el registro.
+    - **LogContent** Alterna citar los mensajes infractores en el canal de registro.
+    - **ExemptUser** Añade o quita usuarios de las exenciones.
+    - **ExemptRole** Añade o quita roles de las exenciones.
+    - **RecordExempt** Alterna registrar los mensajes de usuarios exentos.
+    - **Warnings** Establece cuántas infracciones reciben solo una advertencia antes de los silencios.
+    - **Markdown** Alterna calcular el hash de los bloques de `code` literalmente, quitar las marcas de `spoilers`, o calcular el hash de las `urls` aparte del texto.
+    - **Links** Alterna detectar enlaces repetidos sin importar el texto que los rodea.
+    - **Emoji** Alterna comprobar los mensajes que solo tienen emojis personalizados o stickers.
+    - **Replies** Alterna calcular el hash de las respuestas junto con el mensaje al que responden.
+    - **Edits** Establece cómo se tratan las ediciones: `check`, `ignore`, `grace [ventana]` o `replace`.
+    - **Reactions** Establece cómo se comprueban las reacciones: `off`, `message` o `channel [ventana]`.
+    - **Retract** Establece cuánto tiempo tienen los autores para borrar un mensaje y quitarlo del conjunto de datos, o `off`.
+    - **DMs** Alterna que el bot envíe mensajes directos, si no se menciona a los usuarios en el canal.
+    - **Notices** Establece a dónde van los avisos de silencios y advertencias: `channel [duración|off]`, `log` o `dm`.
+    - **Template** Personaliza los mensajes de `stunlock` y `unmute`: `title`, `description`, `color`, `show` o `reset`.
+
+setting-current-prefix = El prefijo de los comandos es: { $prefix }
+setting-changed-prefix = Se cambió el prefijo de los comandos a: { $prefix }
+setting-added-channel =
+    Se añadió `{ $channel }` a la lista de canales
+    Usa el comando backfill para registrar sus mensajes existentes
+setting-removed-channel = Se quitó `{ $channel }` de la lista de canales
+setting-no-channel = El argumento indicado no es un canal.
+setting-malformed = El argumento indicado no es válido.
+setting-enabled-global = Este servidor usará ahora el conjunto de datos global.
+setting-disabled-global = Este servidor ya no usará el conjunto de datos global
+setting-set-log-channel = Los eventos de moderación se registrarán ahora en { $channel }
+setting-disabled-log-channel = Los eventos de moderación ya no se registrarán.
+setting-enabled-log-content = Los mensajes infractores se citarán ahora en el canal de registro.
+setting-disabled-log-content = Los mensajes infractores ya no se citarán en el canal de registro.
+setting-added-exemption = { $target } está ahora exento
+setting-removed-exemption = { $target } ya no está exento
+setting-no-user = El argumento indicado no es un usuario.
+setting-no-role = El argumento indicado no es un rol.
+setting-enabled-record-exempt = Los mensajes de usuarios exentos se registrarán ahora.
+setting-disabled-record-exempt = Los mensajes de usuarios exentos ya no se registrarán.
+setting-current-warnings = Los usuarios reciben `{ $warnings }` advertencias antes de ser silenciados
+setting-changed-warnings = Los usuarios recibirán ahora `{ $warnings }` advertencias antes de ser silenciados
+setting-whitelist-mode = R9k solo se aplica ahora en los canales y categorías de la lista.
+setting-blacklist-mode = R9k se aplica ahora en todos los canales excepto los canales y categorías de la lista.
+setting-changed-retract = Los mensajes que su autor borre en menos de `{ $window }` ya no cuentan para el conjunto de datos
+setting-disabled-retract = Los mensajes borrados permanecerán en el conjunto de datos para siempre.
+setting-current-edit-policy = Política de ediciones: `{ $policy }`, { $explanation }
+setting-changed-edit-policy = Se cambió la política de ediciones a `{ $policy }`, { $explanation }
+setting-edit-check = las ediciones se comprueban como mensajes nuevos
+setting-edit-ignore = las ediciones nunca se comprueban
+setting-edit-grace = las ediciones solo se comprueban durante `{ $window }` tras el envío
+setting-edit-replace = las ediciones reemplazan al mensaje original en el conjunto de datos
+setting-current-reaction-mode = Modo de reacciones: `{ $mode }`, { $explanation }
+setting-changed-reaction-mode = Se cambió el modo de reacciones a `{ $mode }`, { $explanation }
+setting-reaction-off = las reacciones nunca se comprueban
+setting-reaction-message = no se puede añadir el mismo emoji dos veces a un mensaje
+setting-reaction-channel = no se puede repetir un emoji en un canal durante `{ $window }`
+setting-enabled-reply-context = Las respuestas solo se compararán ahora con respuestas al mismo mensaje.
+setting-disabled-reply-context = Las respuestas se compararán ahora sin importar a qué responden.
+setting-current-markdown =
+    Código literal: `{ $code }`
+    Quitar marcas de spoiler: `{ $spoilers }`
+    URLs aparte: `{ $urls }`
+setting-changed-markdown = { $option } ahora está { $state }
+markdown-option-code = El código literal
+markdown-option-spoilers = Quitar marcas de spoiler
+markdown-option-urls = Separar las URLs
+state-on = activado
+state-off = desactivado
+setting-enabled-links = Los enlaces repetidos se detectarán ahora sin importar el texto que los rodea.
+setting-disabled-links = Los enlaces ahora solo cuentan como parte del texto que los rodea.
+setting-enabled-emoji = Los mensajes con solo emojis personalizados o stickers se comprobarán ahora según cuáles contengan.
+setting-disabled-emoji = Los mensajes con solo emojis personalizados o stickers ya no se distinguirán.
+setting-current-language = El idioma de este servidor es `{ $language }`, idiomas disponibles: { $available }
+setting-changed-language = El idioma de este servidor es ahora `{ $language }`
+setting-unknown-language = Ese idioma no está disponible, idiomas disponibles: { $available }
+setting-enabled-dms = Los usuarios recibirán ahora mensajes directos, salvo que los hayan desactivado.
+setting-disabled-dms = Los usuarios ya no recibirán mensajes directos, se les mencionará en el canal.
+setting-current-notices = Avisos: `{ $mode }`, { $explanation }
+setting-changed-notices = Se cambiaron los avisos a `{ $mode }`, { $explanation }
+setting-notice-channel = los avisos se publican en el canal y se borran tras `{ $lifetime }`
+setting-notice-kept = los avisos se publican en el canal y se conservan
+setting-notice-log = los avisos solo se envían al canal de registro
+setting-notice-dm = los avisos se envían al usuario infractor por mensaje directo
+setting-no-log-channel = Configura un canal de registro antes de enviarle los avisos.
+setting-current-template =
+    **Mensaje**: `{ $embed }`
+    **Visible**: `{ $shown }`
+    **Color**: { $color }
+    **Título**: { $title }
+    **Descripción**: { $description }
+    **Marcadores**: { $placeholders }
+setting-changed-template = Se cambió `{ $field }` del mensaje { $embed }
+setting-reset-template = El mensaje { $embed } vuelve a ser el predeterminado
+setting-unknown-template = Indica qué mensaje personalizar: { $available }
+template-default = predeterminado
+template-too-long = Las plantillas pueden tener como máximo `{ $limit }` caracteres.
+template-unclosed = Hay una `{` sin cerrar, usa `{{` para una llave literal.
+template-unopened = Hay una `}` sin abrir, usa `}}` para una llave literal.
+template-unknown-placeholder = { $placeholder } no es un marcador, marcadores disponibles: { $available }
+
+whitelist-empty-blacklist =
+    No hay canales en la lista negra, r9k se aplica en todas partes.
+    Usa /settings whitelist #canal
+whitelist-empty =
+    No hay canales en la lista blanca.
+    Usa /settings whitelist #canal
+whitelist-blacklist-header = Modo lista negra, r9k se aplica en todas partes excepto:
+whitelist-header = Canales en la lista blanca:
+exempt-empty =
+    No hay usuarios ni roles exentos.
+    Usa /settings exemptuser @usuario o /settings exemptrole @rol
+exempt-header = Usuarios y roles exentos:
+
+## Stunlocks
+
+stunlock-title = Silenciado
+stunlock-automatic =
+    { $user } **fue silenciado durante**: `{ $duration }`
+    **Racha actual**: `{ $streak }`
+stunlock-manual =
+    { $user } **fue silenciado durante**: `{ $duration }`
+    **Racha actual**: `{ $streak }`
+    **Moderador**: { $moderator }
+    **Motivo**: { $reason }
+stunlock-reaction =
+    { $user } **fue silenciado durante**: `{ $duration }`
+    **Motivo**: Reaccionar con { $emoji } después de que otra persona ya lo hiciera
+stunlock-why = [¿Por qué me silenciaron?](https://github.com/DontStarve72/Ninethousand-Eighty-Four#why-was-i-muted)
+warning-title = Advertencia
+warning-description =
+    { $user } **tu mensaje no era original y se ha eliminado.**
+    **Advertencias restantes antes de los silencios**: `{ $remaining }`
+warning-why = [¿Por qué se eliminó mi mensaje?](https://github.com/DontStarve72/Ninethousand-Eighty-Four#why-was-i-muted)
+stunlock-dm-title-guild = Te silenciaron en { $guild }
+stunlock-dm-title = Te silenciaron
+stunlock-dm-description =
+    **Duración**: `{ $duration }`
+    **Moderador**: { $moderator }
+    **Motivo**: { $reason }
+no-reason = No se indicó un motivo.
+reason-automatic = Enviar un mensaje que no era original
+reason-reaction = Reaccionar con { $emoji } después de que otra persona ya lo hiciera
+unmute-guild = **Tu silencio en** `{ $guild }` **ha terminado**.
+unmute = Tu silencio ha terminado.
+dms-enabled = El bot volverá a enviarte mensajes directos.
+dms-disabled = El bot ya no te enviará mensajes directos, se te mencionará en el canal.
+
+## Statistics
+
+stats-title = Estadísticas
+stats-counters =
+    **Comprobados**: `{ $checked }`
+    **Registrados**: `{ $recorded }`
+    **Infracciones**: `{ $violations }`
+    **Silencios**: `{ $mutes }`
+stats-status = Estado
+stats-muted = **Silenciados ahora**: `{ $muted }`
+stats-longest-streak = **Racha activa más larga**: `{ $streak }` de { $user }
+stats-no-streak = **Racha activa más larga**: `0`
+stats-dm-failures = **Mensajes directos no entregados**: `{ $failures }`
+stats-top-offenders = Mayores infractores
+leaderboard-title = Clasificación
+leaderboard-empty = Nadie ha enviado todavía un mensaje en un canal de la lista blanca.
+leaderboard-entry = { $rank }. { $user } `{ $original }` originales, `{ $violations }` infracciones (`{ $ratio }%`)
+profile-title = Perfil
+profile-description =
+    { $user }
+    **Mensajes originales**: `{ $original }`
+    **Infracciones**: `{ $violations }`
+    **Originalidad**: `{ $ratio }%`
+    **Racha actual**: `{ $streak }`
+    **Silencios**: `{ $mutes }`
+profile-decay = **La racha baja en**: `{ $duration }`
+
+## Backfill
+
+backfill-title = Historial
+backfill-running =
+    Registrando el historial de { $channel }...
+    **Mensajes procesados**: `{ $count }`
+backfill-done =
+    Se terminó de registrar el historial de { $channel }.
+    **Mensajes procesados**: `{ $count }`
+backfill-cancelled =
+    Se canceló el registro del historial de { $channel }, vuelve a usar el comando para continuar.
+    **Mensajes procesados**: `{ $count }`
+backfill-failed =
+    No se pudo obtener el historial de { $channel }, vuelve a usar el comando para continuar.
+    **Mensajes procesados**: `{ $count }`
+backfill-already-running = Ya se está registrando el historial de ese canal.
+backfill-not-running = No se está registrando el historial de ese canal.
+backfill-cancelling = Cancelando el registro del historial, el progreso se ha guardado.
+
+## Check
+
+check-original = Ese mensaje sería **original**.
+check-unoriginal = Ese mensaje **no** sería original.
+check-not-enforced = Ese canal no está en la lista blanca, así que allí se permite cualquier mensaje.
+
+## Moderation
+
+manual-mute = Se silenció manualmente a { $user }.
+manual-mute-duration = Se silenció manualmente a { $user } durante `{ $duration }`.
+manual-mute-reason = Motivo
+manual-streak = Se cambió la racha del usuario { $user } a `{ $streak }`
+history-title = Historial
+history-empty = { $user } no tiene infracciones registradas.
+history-header = Historial de infracciones de { $user }:
+history-entry = <t:{ $time }:f> en { $channel }
+history-kind = **{ $kind }**, racha `{ $streak }`
+history-kind-duration = **{ $kind }** durante `{ $duration }`, racha `{ $streak }`
+history-moderator = por { $moderator }
+history-reason = **Motivo**: { $reason }
+history-hash = **Hash**: `{ $hash }`
+history-page = Página { $page } de { $pages }
+history-kind-automatic = automático
+history-kind-manual = manual
+history-kind-pardon = perdón
+history-kind-warning = advertencia
+history-kind-reaction = reacción
+
+## Log
+
+log-automatic-title = Silencio automático
+log-manual-title = Silencio manual
+log-reaction-title = Silencio por reacción
+log-warning-title = Advertencia
+log-pardon-title = Perdón
+log-streak-title = Racha cambiada
+log-setting-title = Ajustes cambiados
+log-whitelist-title = Lista blanca cambiada
+log-mute =
+    { $user } fue silenciado en { $channel } durante `{ $duration }`
+    **Racha**: `{ $streak }`
+log-hash = **Hash**: `{ $hash }`
+log-content =
+    **Contenido**:
+    { $content }
+log-no-content = *Sin contenido de texto*
+log-moderator =
+    **Moderador**: { $moderator }
+    **Motivo**: { $reason }
+log-reaction = **Reacción**: { $emoji }
+log-warning =
+    { $user } recibió una advertencia en { $channel }
+    **Advertencias restantes**: `{ $remaining }`
+log-streak = { $moderator } cambió la racha de { $user } de `{ $old }` a `{ $new }`
+log-setting = { $moderator }: { $change }
+log-channel-added = { $moderator } añadió { $channel } a la lista de canales
+log-channel-removed = { $moderator } quitó { $channel } de la lista de canales
+log-change-prefix = Cambió el prefijo de los comandos a `{ $prefix }`
+log-change-global-enabled = Activó el conjunto de datos global
+log-change-global-disabled = Desactivó el conjunto de datos global
+log-change-blacklist = Cambió al modo lista negra
+log-change-whitelist = Cambió al modo lista blanca
+log-change-warnings = Fijó las advertencias antes de silenciar en `{ $warnings }`
+log-change-retract = Fijó el margen para retirar mensajes en `{ $window }`
+log-change-retract-off = Desactivó la retirada de mensajes borrados
+log-change-edit-policy = Fijó la política de ediciones en `{ $policy }`
+log-change-dms-enabled = Activó los mensajes directos
+log-change-dms-disabled = Desactivó los mensajes directos
+log-change-notice-mode = Fijó el modo de avisos en `{ $mode }`
+log-change-language = Fijó el idioma en `{ $language }`
+log-change-reaction-mode = Fijó el modo de reacciones en `{ $mode }`
+log-change-log-channel = Fijó el canal de registro en { $channel }
+log-change-log-channel-off = Desactivó el canal de registro
+log-change-log-content-enabled = Activó citar los mensajes infractores en el registro
+log-change-log-content-disabled = Desactivó citar los mensajes infractores en el registro
+log-change-reply-context-enabled = Activó comparar las respuestas junto con el mensaje al que responden
+log-change-reply-context-disabled = Desactivó comparar las respuestas junto con el mensaje al que responden
+log-change-markdown = { $option }: { $state }
+log-change-links-enabled = Activó la detección de enlaces repetidos
+log-change-links-disabled = Desactivó la detección de enlaces repetidos
+log-change-emoji-enabled = Activó comprobar los mensajes con solo emojis y stickers
+log-change-emoji-disabled = Desactivó comprobar los mensajes con solo emojis y stickers
+log-change-template-reset = Restableció el embed `{ $embed }`
+log-change-template = Cambió el campo `{ $field }` del embed `{ $embed }`
+log-change-exemption-added = Añadió la exención de { $target }
+log-change-exemption-removed = Quitó la exención de { $target }
+log-change-record-exempt-enabled = Activó registrar los mensajes de usuarios exentos
+log-change-record-exempt-disabled = Desactivó registrar los mensajes de usuarios exentos
+
+## Errors
+
+error-no-channel = Debes indicar un canal de un servidor en el que estés.
+error-no-message = Debes indicar el mensaje que quieres comprobar.
+error-no-user = Debes indicar un usuario. Consulta `{ $prefix }help` para ver ejemplos.
+error-no-int = Debes indicar un número. Consulta `{ $prefix }help` para ver ejemplos.
+error-streak-size = La racha indicada debe ser `al menos 0` y `menor que 16`.
+error-rate-limited = Lo estás haciendo demasiado a menudo, inténtalo de nuevo en `{ $duration }`.
+error-unknown-command = Comando desconocido.
+error-guild-only = Este comando solo se puede usar en servidores.
+error-no-permission = No tienes permiso para usar este comando.
+error-specify-user = Debes indicar un usuario.
+error-malformed-duration = La duración indicada no es válida, prueba algo como `30m` o `1h30m`.
+error-specify-setting = Debes indicar un ajuste.
+error-unknown-setting = Ajuste desconocido.
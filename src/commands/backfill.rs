@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use serenity::{
+    framework::standard::{
+        macros::command,
+        Args,
+        CommandResult
+    },
+    client::Context,
+    model::{
+        channel::Message,
+        id::ChannelId
+    }
+};
+
+use crate::util::{
+    backfill,
+    embeds::{self, Backfill, Setting},
+};
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_GUILD)]
+// Usage: backfill #channel, or backfill cancel #channel
+// Starting a backfill on a channel that was cancelled earlier resumes it
+async fn backfill(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(gid) => gid.0,
+        None => return Ok(()),
+    };
+
+    let cancelling = match args.current() {
+        Some(a) if a.eq_ignore_ascii_case("cancel") => {
+            args.advance();
+            true
+        }
+        _ => false,
+    };
+
+    let channel_id = match args.single::<ChannelId>() {
+        Ok(id) => id,
+        Err(_) => {
+            embeds::setting(ctx, msg, Setting::NoChannel, &[]).await;
+            return Ok(());
+        }
+    };
+
+    // Only channels in this guild can be backfilled
+    match channel_id.to_channel(&ctx.http).await {
+        Ok(channel) => match channel.guild() {
+            Some(c) if c.guild_id.0 == guild_id => {},
+            _ => {
+                embeds::setting(ctx, msg, Setting::NoChannel, &[]).await;
+                return Ok(());
+            }
+        },
+        Err(_) => {
+            embeds::setting(ctx, msg, Setting::NoChannel, &[]).await;
+            return Ok(());
+        }
+    }
+
+    if cancelling {
+        if backfill::cancel(ctx, channel_id).await {
+            embeds::backfill(ctx, msg, Backfill::Cancelling).await;
+        } else {
+            embeds::backfill(ctx, msg, Backfill::NotRunning).await;
+        }
+    } else if !backfill::start(Arc::new(ctx.clone()), guild_id, channel_id, msg.channel_id).await {
+        embeds::backfill(ctx, msg, Backfill::AlreadyRunning).await;
+    }
+
+    Ok(())
+}
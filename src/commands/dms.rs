@@ -0,0 +1,39 @@
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::command,
+        Args,
+        CommandResult,
+    },
+    model::channel::Message,
+};
+
+use crate::{
+    util::{
+        embeds,
+        dms,
+    },
+    Database,
+};
+
+#[command]
+#[aliases(dm, directmessages)]
+// Usage: dms [on|off], toggles without an argument
+// Works in DMs as well, since the preference applies to every guild
+async fn dms(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let user_id = msg.author.id.0;
+
+    let opted_out = match args.single::<String>().map(|a| a.to_lowercase()) {
+        Ok(a) if a == "on" => false,
+        Ok(a) if a == "off" => true,
+        _ => !dms::is_opted_out(database, user_id).await,
+    };
+    dms::set_opted_out(database, user_id, opted_out).await;
+    drop(data);
+
+    embeds::dm_preference(ctx, msg, opted_out).await;
+
+    Ok(())
+}
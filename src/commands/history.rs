@@ -0,0 +1,55 @@
+use serenity::{
+    framework::standard::{
+        macros::command,
+        Args,
+        CommandResult
+    },
+    client::Context,
+    model::{
+        channel::Message,
+        id::UserId
+    }
+};
+
+use crate::{
+    util::{
+        embeds,
+        history::{self, HISTORY_PAGE_SIZE},
+    },
+    Database,
+};
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions(MANAGE_MESSAGES)]
+#[aliases(violations, record)]
+// Usage: history @user [page]
+async fn history(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(gid) => gid.0,
+        None => return Ok(()),
+    };
+
+    let user_id = match args.single::<UserId>() {
+        Ok(u) => u.0,
+        Err(_) => {
+            embeds::no_user(ctx, msg).await;
+            return Ok(());
+        }
+    };
+
+    // Pages are one indexed for the user, zero indexed internally
+    let page = args.single::<u64>().unwrap_or(1).max(1) - 1;
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    let total = history::count(database, guild_id, user_id).await;
+    let pages = total.div_ceil(HISTORY_PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+    let entries = history::page(database, guild_id, user_id, page).await;
+
+    embeds::history(ctx, msg, &user_id, &entries, page + 1, pages).await;
+
+    Ok(())
+}
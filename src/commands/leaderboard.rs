@@ -0,0 +1,75 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::{
+    framework::standard::{
+        macros::command,
+        Args,
+        CommandResult
+    },
+    client::Context,
+    model::{
+        channel::Message,
+        id::UserId
+    }
+};
+
+use crate::{
+    util::{
+        embeds::{self, Profile},
+        stats,
+    },
+    Database, MuteCache,
+};
+
+#[command]
+#[only_in(guilds)]
+#[aliases(top, lb)]
+async fn leaderboard(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(gid) => gid.0,
+        None => return Ok(()),
+    };
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let users = stats::leaderboard(database, guild_id, 10).await;
+
+    embeds::leaderboard(ctx, msg, &users).await;
+
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[aliases(score)]
+// Usage: profile [@user], defaults to the author
+async fn profile(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(gid) => gid.0,
+        None => return Ok(()),
+    };
+
+    let user_id = args.single::<UserId>().map(|u| u.0).unwrap_or(msg.author.id.0);
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let user_stats = stats::user(database, guild_id, user_id).await;
+    let mutes = stats::mute_count(database, guild_id, user_id).await;
+
+    // Streaks decay by 1 every 6 hours
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
+    let (streak, decay_in) = match mute_arc.read().await.get(&guild_id).and_then(|g| g.get(&user_id)) {
+        Some(info) if info.streak > 0 => (info.streak, Some((info.streak_time + 21600).saturating_sub(now))),
+        _ => (0, None),
+    };
+
+    embeds::profile(ctx, msg, Profile {
+        stats: user_stats,
+        streak,
+        mutes,
+        decay_in,
+    }).await;
+
+    Ok(())
+}
@@ -1,4 +1,11 @@
 pub mod meta;
 pub mod settings;
 pub mod mute;
-pub mod set_streak;
\ No newline at end of file
+pub mod set_streak;
+pub mod history;
+pub mod slash;
+pub mod originality;
+pub mod stats;
+pub mod leaderboard;
+pub mod backfill;
+pub mod dms;
\ No newline at end of file
@@ -7,43 +7,58 @@ use serenity::{
     model::{
         channel::Message,
         id::UserId
-    }, 
-    client::Context, 
+    },
+    client::Context,
 };
 
-use crate::{
-    events::on_message::FauxMessage,
-    util::{
-        embeds,
-        check::mute,
-    }
+use crate::util::{
+    embeds,
+    check::{mute, Penalty, MAX_MUTE_DURATION},
+    misc::string_to_seconds,
 };
 
 
 #[command]
+#[only_in(guilds)]
 #[required_permissions(MANAGE_MESSAGES)]
 #[aliases(mute, stunlock)]
+// Usage: mute @user [duration] [reason...]
+// Without a duration the mute behaves like an automatic violation and follows the streak curve
 async fn mute_command(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(id) => id.0,
+        None => return Ok(()),
+    };
+
     let arg = args.single::<UserId>();
 
     match arg {
         Ok(offender_id) => {
             match offender_id.to_user(&ctx.http).await {
                 Ok(offender) => {
-                    embeds::manual_mute(ctx, msg, &offender).await;
-
-                    // This is scuffed
-                    // Neither the content nor the id need to passed to the mute function
-                    // So the content is blank and the message id is syphoned from the muter's message
-                    let fmsg: FauxMessage = FauxMessage {
-                        content: "".to_string(),
-                        author: offender,
-                        id: msg.id,
-                        channel_id: msg.channel_id,
-                        guild_id: msg.guild_id.unwrap().0, // TODO: Check if the bot will try to mute from DMs
+                    // The duration is optional, so only consume the argument if it actually is one
+                    let duration = match args.current().and_then(string_to_seconds) {
+                        Some(d) => {
+                            args.advance();
+                            Some(d.min(MAX_MUTE_DURATION))
+                        }
+                        None => None,
+                    };
+
+                    let reason = match args.rest().trim() {
+                        "" => None,
+                        r => Some(r.to_string()),
                     };
 
-                    mute(ctx, &fmsg).await;
+                    embeds::manual_mute(ctx, msg, &offender, duration, &reason).await;
+
+                    let penalty = Penalty::Manual {
+                        moderator: msg.author.id.0,
+                        duration,
+                        reason,
+                    };
+
+                    mute(ctx, guild_id, msg.channel_id, &offender, &penalty).await;
                 }
 
                 Err(why) => {
@@ -56,6 +71,6 @@ async fn mute_command(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
             embeds::no_user(ctx, msg).await;
         }
     }
-    
+
     Ok(())
 }
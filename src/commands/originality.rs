@@ -0,0 +1,85 @@
+use serenity::{
+    framework::standard::{
+        macros::command,
+        Args,
+        CommandResult
+    },
+    client::Context,
+    model::{
+        channel::Message,
+        id::ChannelId
+    }
+};
+
+use crate::{
+    events::on_message::Fingerprinter,
+    util::embeds::{self, Originality},
+    Database,
+};
+
+#[command]
+#[bucket = "originality"]
+#[aliases(check, original)]
+// Usage: check #channel text...
+// Works in DMs as well, so the text doesn't have to be revealed in the server
+async fn originality(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let channel_id = match args.single::<ChannelId>() {
+        Ok(id) => id,
+        Err(_) => {
+            embeds::originality(ctx, msg, Originality::NoChannel).await;
+            return Ok(());
+        }
+    };
+
+    let content = args.rest();
+    if content.trim().is_empty() {
+        embeds::originality(ctx, msg, Originality::NoText).await;
+        return Ok(());
+    }
+
+    // The guild is taken from the channel, so that the command can be used from DMs
+    let guild_id = match channel_id.to_channel(&ctx.http).await {
+        Ok(channel) => match channel.guild() {
+            Some(c) => c.guild_id,
+            None => {
+                embeds::originality(ctx, msg, Originality::NoChannel).await;
+                return Ok(());
+            }
+        },
+        Err(_) => {
+            embeds::originality(ctx, msg, Originality::NoChannel).await;
+            return Ok(());
+        }
+    };
+
+    // Only members of the guild may probe its dataset
+    if guild_id.member(ctx, msg.author.id).await.is_err() {
+        embeds::originality(ctx, msg, Originality::NoChannel).await;
+        return Ok(());
+    }
+
+    let fingerprinter = Fingerprinter::new(ctx, guild_id.0, channel_id).await;
+    let result = match fingerprinter.fingerprint(content, None, &[]) {
+        Some((true, hash)) => {
+            let data = ctx.data.read().await;
+            let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+            // Never record anything, only look
+            let mut unoriginal = database.row_exists("global", "id", &hash).await;
+            for link_hash in fingerprinter.links(content) {
+                unoriginal = unoriginal || database.row_exists("global", "id", &link_hash).await;
+            }
+
+            if unoriginal {
+                Originality::Unoriginal
+            } else {
+                Originality::Original
+            }
+        }
+        _ => Originality::NotEnforced,
+    };
+
+    embeds::originality(ctx, msg, result).await;
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use std::time::{UNIX_EPOCH, SystemTime};
+use std::{
+    time::{UNIX_EPOCH, SystemTime},
+    collections::HashMap,
+};
 
 use serenity::{
     framework::standard::{
@@ -13,9 +16,18 @@ use serenity::{
     }
 };
 
-use crate::{util::{embeds, misc::{to_string, check_msg}, database::INTEGER}, Database, MuteCache};
+use crate::{
+    util::{
+        embeds::{self, LogEntry},
+        misc::to_string,
+        check::{stunlock_table, MuteInfo},
+        history::{self, HistoryKind, HistoryRecord},
+    },
+    Database, MuteCache,
+};
 
 #[command]
+#[only_in(guilds)]
 #[required_permissions(MANAGE_MESSAGES)]
 #[aliases(setstreak, streak)]
 async fn set_streak(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
@@ -38,44 +50,7 @@ async fn set_streak(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
     let arg_int = args.single::<u64>();
     match arg_int {
         Ok(streak) => {
-            if streak <= 16 {
-                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-
-                let data = ctx.data.read().await;
-
-                // Record in DB
-                let database = data.get::<Database>().expect("Expected Database in TypeMap");
-
-
-                let stunlock_table = &format!("stunlocks_{}", guild_id);
-                if !database.table_exists(stunlock_table).await {
-                    database.create_table(stunlock_table, &vec!["id", "streak", "streak_time", "mute_until"], &vec![INTEGER, INTEGER, INTEGER, INTEGER]).await;
-                }
-
-                if database.row_exists(stunlock_table, "id", &user_id).await {
-                    database.update_int(stunlock_table, "streak", &streak, &user_id).await;
-                    database.update_int(stunlock_table, "streak_time", &now, &user_id).await;
-                } else {
-                    database.insert_row(stunlock_table, &[&to_string(user_id), &to_string(streak), &to_string(now), &to_string(0)]).await;
-                }
-
-                // Record in Cache
-                let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
-                let mut mute_cache = mute_arc.write().await;
-
-                // TODO: Get rid of the FauxMessage terribleness and make it possible to set the streak of
-                // users not in the database/guilds with no database.
-                if let Some(guild_data) = mute_cache.get_mut(&guild_id) {
-                    if let Some(user_data) = guild_data.get_mut(&user_id) {
-                        user_data.streak = streak;
-                        user_data.streak_time = now;
-                    } else {
-                        check_msg(msg.channel_id.say(&ctx.http ,"`Placeholder` You cannot currently set the streak of someone who has never been muted before.").await);
-                    }
-                } else {
-                    check_msg(msg.channel_id.say(&ctx.http ,"`Placeholder` You cannot currently set streaks in a guild that no one has been muted in before.").await);
-                }
-                
+            if apply_streak(ctx, guild_id, msg.channel_id.0, msg.author.id.0, user_id, streak).await {
                 embeds::manual_streak(ctx, msg, &user_id, &streak).await;
             } else {
                 embeds::streak_bad_size(ctx, msg).await;
@@ -85,4 +60,63 @@ async fn set_streak(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Shared between the prefix and slash commands, returns false if the streak is out of bounds
+pub async fn apply_streak(ctx: &Context, guild_id: u64, channel_id: u64, moderator: u64, user_id: u64, streak: u64) -> bool {
+    if streak > 16 {
+        return false;
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    let data = ctx.data.read().await;
+
+    // Record in DB
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let stunlock_table = &stunlock_table(database, &guild_id).await;
+
+    let old_streak: u64;
+    if database.row_exists(stunlock_table, "id", &user_id).await {
+        old_streak = database.retrieve_int(stunlock_table, "streak", "id", &user_id).await as u64;
+        database.update_int(stunlock_table, "streak", &streak, &user_id).await;
+        database.update_int(stunlock_table, "streak_time", &now, &user_id).await;
+    } else {
+        old_streak = 0;
+        database.insert_row(stunlock_table, &[&to_string(user_id), &to_string(streak), &to_string(now), &to_string(i64::MAX as u64), &to_string(0), "", &to_string(0), &to_string(0)]).await;
+    }
+
+    history::record(database, HistoryRecord {
+        guild: guild_id,
+        user: user_id,
+        channel: channel_id,
+        // Lowering a streak is a pardon, raising it is a manual punishment without a mute
+        kind: if streak < old_streak { HistoryKind::Pardon } else { HistoryKind::Manual },
+        duration: 0,
+        streak,
+        hash: None,
+        moderator,
+        reason: "",
+    }).await;
+
+    embeds::log(ctx, &guild_id, LogEntry::Streak {
+        moderator,
+        user: user_id,
+        old: old_streak,
+        new: streak,
+    }).await;
+
+    // Record in Cache
+    let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
+    let mut mute_cache = mute_arc.write().await;
+
+    let new_user = MuteInfo::new_mute().await;
+    let user_data = mute_cache.entry(guild_id)
+        .or_insert_with(HashMap::new)
+        .entry(user_id)
+        .or_insert(new_user);
+    user_data.streak = streak;
+    user_data.streak_time = now;
+
+    true
+}
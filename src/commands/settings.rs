@@ -5,16 +5,23 @@ use serenity::{
         CommandResult,
         Args, ArgError,
     },
-    model::{channel::{Message}, id::ChannelId},
+    model::{channel::{Message, Channel}, id::{ChannelId, UserId, RoleId}},
 };
 
 use crate::{
+    events::on_message::{EditPolicy, MAX_EDIT_WINDOW},
     util::{
-        misc::to_string, 
-        embeds::{Setting, self}, 
+        misc::{to_string, string_to_seconds, seconds_to_string},
+        embeds::{Setting, LogEntry, SettingChange, self}, 
+        reactions::{ReactionMode, MAX_REACTION_WINDOW},
+        notices::{self, NoticeMode},
+        template::{self, TemplateKind},
+        locale,
         database::{INTEGER},
+        check::MAX_WARNINGS,
+        messages::MAX_RETRACT_WINDOW,
     }, 
-    Database,
+    Database, PrefixCache,
 };
 
 #[command]
@@ -35,97 +42,574 @@ async fn settings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
         Ok(s) => match s.to_lowercase().as_str() {
             // Prefix Setting
             "prefix" => {
+                // If a Prefix is specififed, change the prefix,
+                // if no prefix is specified, say the current prefix for the server.
+                let (setting, values) = change_prefix(ctx, guild_id, msg.author.id.0, args.single::<String>().ok()).await;
+                embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+            },
+
+            //Whitelist channels setting
+            "whitelist" => {
+                match args.single::<ChannelId>() {
+                    Ok(id) => {
+                        let (setting, values) = toggle_whitelist(ctx, guild_id, msg.author.id.0, id).await;
+                        embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+                    }
+                    Err(err) => {
+                        match err {
+                            ArgError::Eos => {
+                                ensure_channel_table(ctx, guild_id).await;
+
+                                // Print a list of all currently whitelist channels
+                                embeds::whitelisted(ctx, msg).await;
+                            }
+                            _ => embeds::setting(ctx, msg, Setting::NoChannel, &[]).await,
+                        }
+                    }
+                }
+            },
+
+            "global" => {
+                let (setting, values) = toggle_global(ctx, guild_id, msg.author.id.0).await;
+                embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+            },
+
+            // Switch between whitelist and blacklist semantics for the channel list
+            "mode" | "blacklist" => {
+                let (setting, values) = toggle_mode(ctx, guild_id, msg.author.id.0).await;
+                embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+            },
+
+            // How long after sending a message its author can delete it to make its content original again
+            "retract" => {
                 let data = ctx.data.read().await;
                 let database = data.get::<Database>().expect("Expected Database in TypeMap");
 
-                let arg1 = args.single::<String>();
-                match arg1 {
-                    // If a Prefix is specififed, change the prefix,
-                    // unless the guild id is 0, in which case return the 0 (default) prefix
-                    Ok(s) => {
-                        if !database.row_exists("guild_settings", "id", &guild_id).await {
-                            database.insert_row("guild_settings", &[&to_string(guild_id), "9!"]).await;
+                let arg = args.single::<String>();
+                match arg {
+                    Ok(a) => {
+                        let window = match a.to_lowercase().as_str() {
+                            "off" | "0" => Some(0),
+                            _ => string_to_seconds(&a).or_else(|| a.parse::<u64>().ok()).map(|w| w.min(MAX_RETRACT_WINDOW)),
+                        };
+                        match window {
+                            Some(w) => {
+                                database.update_int("guild_settings", "retract_window", &w, &guild_id).await;
+                                if w == 0 {
+                                    embeds::setting(ctx, msg, Setting::DisabledRetract, &[]).await;
+                                } else {
+                                    embeds::setting(ctx, msg, Setting::ChangedRetract, &[&seconds_to_string(w)]).await;
+                                }
+                                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                                    moderator: msg.author.id.0,
+                                    change: SettingChange::Retract(w),
+                                }).await;
+                            }
+                            None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
                         }
-                        database.update_str("guild_settings", "prefix", &s, &guild_id).await;
-                        embeds::setting(ctx, msg, Setting::ChangedPrefix,&[&s]).await;
                     }
-
-                    // if no prefix is specified, say the current prefix for the server.
                     Err(_) => {
-                        if !database.row_exists("guild_settings", "id", &guild_id).await {
-                            database.insert_row("guild_settings", &[&to_string(guild_id), "9!"]).await;
+                        let w = database.retrieve_int("guild_settings", "retract_window", "id", &guild_id).await as u64;
+                        if w == 0 {
+                            embeds::setting(ctx, msg, Setting::DisabledRetract, &[]).await;
+                        } else {
+                            embeds::setting(ctx, msg, Setting::ChangedRetract, &[&seconds_to_string(w)]).await;
                         }
-                        let prefix = database.retrieve_str("guild_settings", "prefix","id", &guild_id).await;
-                        embeds::setting(ctx, msg, Setting::CurrentPrefix, &[&prefix]).await;
                     }
                 }
             },
 
-            //Whitelist channels setting
-            "whitelist" => {
-                let arg1 = args.single::<ChannelId>();
+            // How edited messages are treated
+            "edits" | "edit" => {
                 let data = ctx.data.read().await;
                 let database = data.get::<Database>().expect("Expected Database in TypeMap");
-                let channel_table = &format!("channels_{}", guild_id);
-                
-                match arg1 {
-                    Ok(id) => {
-                            match id.to_channel(&ctx.http).await {
-                                Ok(channel) => {
-                                    if let Some(c) = channel.guild() {
-                                        if !database.table_exists(channel_table).await {
-                                            database.create_table(channel_table, &["id"], &vec![INTEGER]).await;
-                                        }
-                                        
-                                        // Channel is whitelisted
-                                        if database.row_exists(channel_table, "id", &id.0).await {
-                                            // Remove from whitelist
-                                            database.delete_row(channel_table, "id", &id.0).await;
-                                            embeds::setting(ctx, msg, Setting::RemovedChannel, &[&c.name]).await;
-                                        }
-                                        // Channel isn't whitelisted 
-                                        else {
-                                            // Add to whitelist
-                                            database.insert_row(channel_table, &[&to_string(&id.0)]).await;
-                                            embeds::setting(ctx, msg, Setting::AddedChannel, &[&c.name]).await;
-                                        }
-                                    } else {
-                                        embeds::setting(ctx, msg, Setting::NoChannel, &[]).await;
-                                    }
+
+                match args.single::<String>() {
+                    Ok(a) => match EditPolicy::parse(&a) {
+                        Some(policy) => {
+                            // The grace window defaults to a minute
+                            let window = match args.single::<String>() {
+                                Ok(w) => string_to_seconds(&w).or_else(|| w.parse::<u64>().ok()).map(|w| w.min(MAX_EDIT_WINDOW)),
+                                Err(_) => Some(60),
+                            };
+                            match window {
+                                Some(w) => {
+                                    database.update_int("guild_settings", "edit_policy", &policy.as_i64(), &guild_id).await;
+                                    database.update_int("guild_settings", "edit_window", &w, &guild_id).await;
+                                    embeds::setting(ctx, msg, Setting::ChangedEditPolicy, &[policy.name(), &seconds_to_string(w)]).await;
+                                    embeds::log(ctx, &guild_id, LogEntry::Setting {
+                                        moderator: msg.author.id.0,
+                                        change: SettingChange::EditPolicy(policy.name()),
+                                    }).await;
                                 }
-                                Err(_) => println!("Unable to get channel [settings whitelist]"),
+                                None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
                             }
                         }
-                    Err(err) => {
-                        match err {
-                            ArgError::Eos => {
-                                if !database.table_exists(channel_table).await {
-                                    database.create_table(channel_table, &vec!["id"], &vec![INTEGER]).await;
+                        None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                    },
+                    Err(_) => {
+                        let policy = EditPolicy::from(database.retrieve_int("guild_settings", "edit_policy", "id", &guild_id).await);
+                        let w = database.retrieve_int("guild_settings", "edit_window", "id", &guild_id).await as u64;
+                        embeds::setting(ctx, msg, Setting::CurrentEditPolicy, &[policy.name(), &seconds_to_string(w)]).await;
+                    }
+                }
+            },
+
+            // Whether the bot DMs users in this guild at all
+            "dms" | "directmessages" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let direct_messages = database.retrieve_bool("guild_settings", "direct_messages", "id", &guild_id).await;
+                database.update_bool("guild_settings", "direct_messages", !direct_messages, &guild_id).await;
+
+                if direct_messages {
+                    embeds::setting(ctx, msg, Setting::DisabledDms, &[]).await;
+                } else {
+                    embeds::setting(ctx, msg, Setting::EnabledDms, &[]).await;
+                }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::DirectMessages(!direct_messages),
+                }).await;
+            },
+
+            // Where stunlock and warning notices go, and how long they stay up in the channel
+            "notices" | "notice" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                match args.single::<String>() {
+                    Ok(a) => match NoticeMode::parse(&a) {
+                        // Log mode without a log channel would leave notices with nowhere to go
+                        Some(NoticeMode::Log) if database.retrieve_int("guild_settings", "log_channel", "id", &guild_id).await == 0 => {
+                            embeds::setting(ctx, msg, Setting::NoLogChannel, &[]).await;
+                        }
+                        Some(mode) => {
+                            // The lifetime only matters in channel mode, `off` keeps notices around
+                            let lifetime = match args.single::<String>() {
+                                Ok(l) if l.eq_ignore_ascii_case("off") => Some(0),
+                                Ok(l) => string_to_seconds(&l).or_else(|| l.parse::<u64>().ok()),
+                                Err(_) => Some(10),
+                            };
+                            match lifetime {
+                                Some(l) => {
+                                    database.update_int("guild_settings", "notice_mode", &mode.as_i64(), &guild_id).await;
+                                    database.update_int("guild_settings", "notice_lifetime", &l, &guild_id).await;
+                                    // An empty lifetime tells the embed notices are kept
+                                    let kept = if l == 0 { String::new() } else { seconds_to_string(l) };
+                                    embeds::setting(ctx, msg, Setting::ChangedNotices, &[mode.name(), &kept]).await;
+                                    embeds::log(ctx, &guild_id, LogEntry::Setting {
+                                        moderator: msg.author.id.0,
+                                        change: SettingChange::NoticeMode(mode.name()),
+                                    }).await;
                                 }
-        
-                                // Print a list of all currently whitelist channels
-                                embeds::whitelisted(ctx, msg).await;
+                                None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
                             }
-                            _ => embeds::setting(ctx, msg, Setting::NoChannel, &[]).await,
                         }
+                        None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                    },
+                    Err(_) => {
+                        let (mode, lifetime) = notices::settings(database, guild_id).await;
+                        let kept = if lifetime == 0 { String::new() } else { seconds_to_string(lifetime) };
+                        embeds::setting(ctx, msg, Setting::CurrentNotices, &[mode.name(), &kept]).await;
                     }
                 }
             },
 
-            "global" => {
+            // The language the bot speaks in this guild
+            "language" | "lang" | "locale" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+                let available = locale::available().join(", ");
+
+                match args.single::<String>() {
+                    Ok(a) => {
+                        let code = a.to_lowercase();
+                        if locale::is_available(&code) {
+                            database.update_str("guild_settings", "language", &code, &guild_id).await;
+                            embeds::setting(ctx, msg, Setting::ChangedLanguage, &[&code]).await;
+                            embeds::log(ctx, &guild_id, LogEntry::Setting {
+                                moderator: msg.author.id.0,
+                                change: SettingChange::Language(code.to_string()),
+                            }).await;
+                        } else {
+                            embeds::setting(ctx, msg, Setting::UnknownLanguage, &[&available]).await;
+                        }
+                    }
+                    Err(_) => {
+                        let code = database.retrieve_str("guild_settings", "language", "id", &guild_id).await;
+                        embeds::setting(ctx, msg, Setting::CurrentLanguage, &[&code, &available]).await;
+                    }
+                }
+            },
+
+            // Whether reactions are checked for originality, and how
+            "reactions" | "reaction" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                match args.single::<String>() {
+                    Ok(a) => match ReactionMode::parse(&a) {
+                        Some(mode) => {
+                            // The window only matters in channel mode, and defaults to five minutes
+                            let window = match args.single::<String>() {
+                                Ok(w) => string_to_seconds(&w).or_else(|| w.parse::<u64>().ok()).map(|w| w.min(MAX_REACTION_WINDOW)),
+                                Err(_) => Some(300),
+                            };
+                            match window {
+                                Some(w) => {
+                                    database.update_int("guild_settings", "reaction_mode", &mode.as_i64(), &guild_id).await;
+                                    database.update_int("guild_settings", "reaction_window", &w, &guild_id).await;
+                                    embeds::setting(ctx, msg, Setting::ChangedReactionMode, &[mode.name(), &seconds_to_string(w)]).await;
+                                    embeds::log(ctx, &guild_id, LogEntry::Setting {
+                                        moderator: msg.author.id.0,
+                                        change: SettingChange::ReactionMode(mode.name()),
+                                    }).await;
+                                }
+                                None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                            }
+                        }
+                        None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                    },
+                    Err(_) => {
+                        let mode = ReactionMode::from(database.retrieve_int("guild_settings", "reaction_mode", "id", &guild_id).await);
+                        let w = database.retrieve_int("guild_settings", "reaction_window", "id", &guild_id).await as u64;
+                        embeds::setting(ctx, msg, Setting::CurrentReactionMode, &[mode.name(), &seconds_to_string(w)]).await;
+                    }
+                }
+            },
+
+            // Log channel setting
+            "log" | "logchannel" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                match args.single::<ChannelId>() {
+                    // Only a text channel in this guild, otherwise events could be logged into another server
+                    Ok(id) if !in_guild(ctx, guild_id, id).await => embeds::setting(ctx, msg, Setting::NoChannel, &[]).await,
+                    Ok(id) => {
+                        database.update_int("guild_settings", "log_channel", &id.0, &guild_id).await;
+                        embeds::setting(ctx, msg, Setting::SetLogChannel, &[&format!("<#{}>", id.0)]).await;
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::LogChannel(id.0),
+                        }).await;
+                    }
+                    Err(ArgError::Eos) => {
+                        // Log to the old channel before it's disabled
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::LogChannel(0),
+                        }).await;
+                        database.update_int("guild_settings", "log_channel", &0u64, &guild_id).await;
+                        embeds::setting(ctx, msg, Setting::DisabledLogChannel, &[]).await;
+                    }
+                    Err(_) => embeds::setting(ctx, msg, Setting::NoChannel, &[]).await,
+                }
+            },
+
+            // Whether violating messages are quoted in the log channel
+            "logcontent" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let log_content = database.retrieve_bool("guild_settings", "log_content", "id", &guild_id).await;
+                database.update_bool("guild_settings", "log_content", !log_content, &guild_id).await;
+
+                if log_content {
+                    embeds::setting(ctx, msg, Setting::DisabledLogContent, &[]).await;
+                } else {
+                    embeds::setting(ctx, msg, Setting::EnabledLogContent, &[]).await;
+                }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::LogContent(!log_content),
+                }).await;
+            },
+
+
+            // Whether replies are hashed together with the message they reply to
+            "replies" | "replycontext" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let reply_context = database.retrieve_bool("guild_settings", "reply_context", "id", &guild_id).await;
+                database.update_bool("guild_settings", "reply_context", !reply_context, &guild_id).await;
+
+                if reply_context {
+                    embeds::setting(ctx, msg, Setting::DisabledReplyContext, &[]).await;
+                } else {
+                    embeds::setting(ctx, msg, Setting::EnabledReplyContext, &[]).await;
+                }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::ReplyContext(!reply_context),
+                }).await;
+            },
+
+
+            // How Markdown is treated when hashing
+            "markdown" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let column = match args.single::<String>() {
+                    Ok(a) => match a.to_lowercase().as_str() {
+                        "code" => Some(("code", "code_verbatim")),
+                        "spoilers" | "spoiler" => Some(("spoilers", "strip_spoilers")),
+                        "urls" | "url" | "links" => Some(("urls", "separate_urls")),
+                        _ => {
+                            embeds::setting(ctx, msg, Setting::Malformed, &[]).await;
+                            return Ok(());
+                        }
+                    },
+                    Err(_) => None,
+                };
+
+                match column {
+                    Some((option, c)) => {
+                        let enabled = !database.retrieve_bool("guild_settings", c, "id", &guild_id).await;
+                        database.update_bool("guild_settings", c, enabled, &guild_id).await;
+                        let state = if enabled { "on" } else { "off" };
+                        embeds::setting(ctx, msg, Setting::ChangedMarkdown, &[option, state]).await;
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::Markdown(option, enabled),
+                        }).await;
+                    }
+                    None => {
+                        let mut states = Vec::new();
+                        for c in ["code_verbatim", "strip_spoilers", "separate_urls"] {
+                            states.push(if database.retrieve_bool("guild_settings", c, "id", &guild_id).await { "on" } else { "off" });
+                        }
+                        embeds::setting(ctx, msg, Setting::CurrentMarkdown, &states).await;
+                    }
+                }
+            },
+
+
+            // Whether links are fingerprinted apart from the text around them
+            "links" | "linkfingerprints" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let link_fingerprints = database.retrieve_bool("guild_settings", "link_fingerprints", "id", &guild_id).await;
+                database.update_bool("guild_settings", "link_fingerprints", !link_fingerprints, &guild_id).await;
+
+                if link_fingerprints {
+                    embeds::setting(ctx, msg, Setting::DisabledLinks, &[]).await;
+                } else {
+                    embeds::setting(ctx, msg, Setting::EnabledLinks, &[]).await;
+                }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::Links(!link_fingerprints),
+                }).await;
+            },
+
+
+            // Whether messages without text are hashed by their custom emoji and stickers
+            "emoji" | "stickers" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let emoji_fingerprints = database.retrieve_bool("guild_settings", "emoji_fingerprints", "id", &guild_id).await;
+                database.update_bool("guild_settings", "emoji_fingerprints", !emoji_fingerprints, &guild_id).await;
+
+                if emoji_fingerprints {
+                    embeds::setting(ctx, msg, Setting::DisabledEmoji, &[]).await;
+                } else {
+                    embeds::setting(ctx, msg, Setting::EnabledEmoji, &[]).await;
+                }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::Emoji(!emoji_fingerprints),
+                }).await;
+            },
+
+
+            // Custom title, description and color for the stunlock and unmute embeds, or hiding them entirely
+            "template" | "templates" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+                let kind = match args.single::<String>().ok().and_then(|k| TemplateKind::parse(&k)) {
+                    Some(k) => k,
+                    None => {
+                        embeds::setting(ctx, msg, Setting::UnknownTemplate, &["`stunlock`, `unmute`"]).await;
+                        return Ok(());
+                    }
+                };
+
+                let mut custom = template::get(database, guild_id, kind).await.unwrap_or_default();
+                let field = args.single::<String>().map(|f| f.to_lowercase());
+                let value = args.rest().trim().to_string();
+                let reset = value.eq_ignore_ascii_case("reset") || value.eq_ignore_ascii_case("default");
+
+                let result = match field.as_deref() {
+                    Err(_) => {
+                        let color = custom.color.map(|c| format!("#{:06x}", c)).unwrap_or_default();
+                        let shown = if custom.shown { "on" } else { "off" };
+                        embeds::setting(ctx, msg, Setting::CurrentTemplate, &[
+                            kind.name(), shown, &color, &custom.title, &custom.description, &template::list(kind.placeholders()),
+                        ]).await;
+                        return Ok(());
+                    }
+                    Ok("reset") => {
+                        template::reset(database, guild_id, kind).await;
+                        embeds::setting(ctx, msg, Setting::ResetTemplate, &[kind.name()]).await;
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::ResetTemplate(kind),
+                        }).await;
+                        return Ok(());
+                    }
+                    _ if value.is_empty() => None,
+                    Ok("title") if reset => {
+                        custom.title.clear();
+                        Some(Ok(()))
+                    }
+                    Ok("title") => Some(template::validate_title(kind, &value).map(|_| custom.title = value.clone())),
+                    Ok("description") | Ok("desc") if reset => {
+                        custom.description.clear();
+                        Some(Ok(()))
+                    }
+                    Ok("description") | Ok("desc") => Some(template::validate_description(kind, &value).map(|_| custom.description = value.clone())),
+                    Ok("color") | Ok("colour") if reset => {
+                        custom.color = None;
+                        Some(Ok(()))
+                    }
+                    Ok("color") | Ok("colour") => template::parse_color(&value).map(|c| {
+                        custom.color = Some(c);
+                        Ok(())
+                    }),
+                    Ok("show") | Ok("shown") => match value.to_lowercase().as_str() {
+                        "on" | "true" | "yes" => {
+                            custom.shown = true;
+                            Some(Ok(()))
+                        }
+                        "off" | "false" | "no" => {
+                            custom.shown = false;
+                            Some(Ok(()))
+                        }
+                        _ => None,
+                    },
+                    Ok(_) => None,
+                };
+
+                match result {
+                    Some(Ok(())) => {
+                        let field = field.unwrap_or_default();
+                        template::set(database, guild_id, kind, &custom).await;
+                        embeds::setting(ctx, msg, Setting::ChangedTemplate, &[kind.name(), &field]).await;
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::Template(kind, field.to_string()),
+                        }).await;
+                    }
+                    Some(Err(why)) => {
+                        let locale = locale::guild_locale(ctx, Some(guild_id)).await;
+                        embeds::setting(ctx, msg, Setting::InvalidTemplate, &[&why.describe(&locale, kind)]).await;
+                    }
+                    None => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                }
+            },
+
+
+            // Users exempt from enforcement
+            "exemptuser" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+                let user_table = &format!("exempt_users_{}", guild_id);
+                if !database.table_exists(user_table).await {
+                    database.create_table(user_table, &["id"], &[INTEGER]).await;
+                }
+
+                match args.single::<UserId>() {
+                    Ok(id) => {
+                        let mention = format!("<@{}>", id.0);
+                        let added = !database.row_exists(user_table, "id", &id.0).await;
+                        if added {
+                            database.insert_row(user_table, &[&to_string(id.0)]).await;
+                            embeds::setting(ctx, msg, Setting::AddedExemption, &[&mention]).await;
+                        } else {
+                            database.delete_row(user_table, "id", &id.0).await;
+                            embeds::setting(ctx, msg, Setting::RemovedExemption, &[&mention]).await;
+                        }
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::Exemption(mention.clone(), added),
+                        }).await;
+                    }
+                    Err(ArgError::Eos) => embeds::exempted(ctx, msg).await,
+                    Err(_) => embeds::setting(ctx, msg, Setting::NoUser, &[]).await,
+                }
+            },
+
+            // Roles exempt from enforcement
+            "exemptrole" => {
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+                let role_table = &format!("exempt_roles_{}", guild_id);
+                if !database.table_exists(role_table).await {
+                    database.create_table(role_table, &["id"], &[INTEGER]).await;
+                }
+
+                match args.single::<RoleId>() {
+                    Ok(id) => {
+                        let mention = format!("<@&{}>", id.0);
+                        let added = !database.row_exists(role_table, "id", &id.0).await;
+                        if added {
+                            database.insert_row(role_table, &[&to_string(id.0)]).await;
+                            embeds::setting(ctx, msg, Setting::AddedExemption, &[&mention]).await;
+                        } else {
+                            database.delete_row(role_table, "id", &id.0).await;
+                            embeds::setting(ctx, msg, Setting::RemovedExemption, &[&mention]).await;
+                        }
+                        embeds::log(ctx, &guild_id, LogEntry::Setting {
+                            moderator: msg.author.id.0,
+                            change: SettingChange::Exemption(mention.clone(), added),
+                        }).await;
+                    }
+                    Err(ArgError::Eos) => embeds::exempted(ctx, msg).await,
+                    Err(_) => embeds::setting(ctx, msg, Setting::NoRole, &[]).await,
+                }
+            },
+
+            // Whether messages from exempt users are recorded in the dataset
+            "recordexempt" => {
                 let data = ctx.data.read().await;
                 let database = data.get::<Database>().expect("Expected Database in TypeMap");
 
-                let global = database.retrieve_bool("guild_settings", "global", "id", &guild_id).await;
-                database.update_bool("guild_settings", "global", !global, &guild_id).await;
+                let record_exempt = database.retrieve_bool("guild_settings", "record_exempt", "id", &guild_id).await;
+                database.update_bool("guild_settings", "record_exempt", !record_exempt, &guild_id).await;
 
-                if global {
-                    embeds::setting(ctx, msg, Setting::DisabledGlobal, &[]).await;
+                if record_exempt {
+                    embeds::setting(ctx, msg, Setting::DisabledRecordExempt, &[]).await;
                 } else {
-                    embeds::setting(ctx, msg, Setting::EnabledGlobal, &[]).await;
+                    embeds::setting(ctx, msg, Setting::EnabledRecordExempt, &[]).await;
                 }
+                embeds::log(ctx, &guild_id, LogEntry::Setting {
+                    moderator: msg.author.id.0,
+                    change: SettingChange::RecordExempt(!record_exempt),
+                }).await;
             },
 
+            // Free violations per decay window before users get stunlocked
+            "warnings" => {
+                match args.single::<u64>() {
+                    Ok(warnings) => {
+                        let (setting, values) = change_warnings(ctx, guild_id, msg.author.id.0, Some(warnings)).await;
+                        embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+                    }
+                    Err(ArgError::Eos) => {
+                        let (setting, values) = change_warnings(ctx, guild_id, msg.author.id.0, None).await;
+                        embeds::setting(ctx, msg, setting, &to_strs(&values)).await;
+                    }
+                    Err(_) => embeds::setting(ctx, msg, Setting::Malformed, &[]).await,
+                }
+            },
 
             // If a setting is specified which doesn't exist, declare as much, and print a list of available settings.
             _ => embeds::list_settings(ctx, msg).await,
@@ -142,3 +626,155 @@ async fn settings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 
     Ok(())
 }
+
+// The functions below are shared between the prefix and slash commands
+// They return which settings embed to respond with along with its arguments
+
+pub fn to_strs(args: &[String]) -> Vec<&str> {
+    args.iter().map(|a| a.as_str()).collect()
+}
+
+pub async fn ensure_channel_table(ctx: &Context, guild_id: u64) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let channel_table = &format!("channels_{}", guild_id);
+    if !database.table_exists(channel_table).await {
+        database.create_table(channel_table, &["id"], &[INTEGER]).await;
+    }
+}
+
+pub async fn change_prefix(ctx: &Context, guild_id: u64, moderator: u64, prefix: Option<String>) -> (Setting, Vec<String>) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    database.ensure_guild(&guild_id).await;
+
+    match prefix {
+        Some(s) => {
+            database.update_str("guild_settings", "prefix", &s, &guild_id).await;
+            let prefix_arc = data.get::<PrefixCache>().expect("Expected PrefixCache in TypeMap");
+            prefix_arc.write().await.insert(guild_id, s.clone());
+            embeds::log(ctx, &guild_id, LogEntry::Setting {
+                moderator,
+                change: SettingChange::Prefix(s.clone()),
+            }).await;
+            (Setting::ChangedPrefix, vec![s])
+        }
+        None => {
+            let prefix = database.retrieve_str("guild_settings", "prefix","id", &guild_id).await;
+            (Setting::CurrentPrefix, vec![prefix])
+        }
+    }
+}
+
+async fn in_guild(ctx: &Context, guild_id: u64, id: ChannelId) -> bool {
+    match id.to_channel(&ctx.http).await {
+        Ok(Channel::Guild(c)) => c.guild_id.0 == guild_id,
+        Ok(_) => false,
+        Err(_) => {
+            println!("Unable to get channel [settings log]");
+            false
+        }
+    }
+}
+
+pub async fn toggle_whitelist(ctx: &Context, guild_id: u64, moderator: u64, id: ChannelId) -> (Setting, Vec<String>) {
+    // Categories can be listed as well, which covers every channel under them
+    let name = match id.to_channel(&ctx.http).await {
+        Ok(Channel::Guild(c)) if c.guild_id.0 == guild_id => c.name,
+        Ok(Channel::Category(c)) if c.guild_id.0 == guild_id => c.name,
+        Ok(_) => return (Setting::NoChannel, vec![]),
+        Err(_) => {
+            println!("Unable to get channel [settings whitelist]");
+            return (Setting::NoChannel, vec![]);
+        }
+    };
+
+    ensure_channel_table(ctx, guild_id).await;
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let channel_table = &format!("channels_{}", guild_id);
+
+    // Channel is whitelisted
+    let added = !database.row_exists(channel_table, "id", &id.0).await;
+    if !added {
+        // Remove from whitelist
+        database.delete_row(channel_table, "id", &id.0).await;
+    }
+    // Channel isn't whitelisted 
+    else {
+        // Add to whitelist
+        database.insert_row(channel_table, &[&to_string(id.0)]).await;
+    }
+
+    embeds::log(ctx, &guild_id, LogEntry::Whitelist {
+        moderator,
+        channel: id.0,
+        added,
+    }).await;
+
+    if added {
+        (Setting::AddedChannel, vec![name])
+    } else {
+        (Setting::RemovedChannel, vec![name])
+    }
+}
+
+pub async fn toggle_global(ctx: &Context, guild_id: u64, moderator: u64) -> (Setting, Vec<String>) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    let global = database.retrieve_bool("guild_settings", "global", "id", &guild_id).await;
+    database.update_bool("guild_settings", "global", !global, &guild_id).await;
+
+    embeds::log(ctx, &guild_id, LogEntry::Setting {
+        moderator,
+        change: SettingChange::Global(!global),
+    }).await;
+
+    if global {
+        (Setting::DisabledGlobal, vec![])
+    } else {
+        (Setting::EnabledGlobal, vec![])
+    }
+}
+
+pub async fn change_warnings(ctx: &Context, guild_id: u64, moderator: u64, warnings: Option<u64>) -> (Setting, Vec<String>) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    match warnings {
+        Some(warnings) => {
+            let warnings = warnings.min(MAX_WARNINGS);
+            database.update_int("guild_settings", "warnings", &warnings, &guild_id).await;
+            embeds::log(ctx, &guild_id, LogEntry::Setting {
+                moderator,
+                change: SettingChange::Warnings(warnings),
+            }).await;
+            (Setting::ChangedWarnings, vec![to_string(warnings)])
+        }
+        None => {
+            let warnings = database.retrieve_int("guild_settings", "warnings", "id", &guild_id).await;
+            (Setting::CurrentWarnings, vec![to_string(warnings)])
+        }
+    }
+}
+
+pub async fn toggle_mode(ctx: &Context, guild_id: u64, moderator: u64) -> (Setting, Vec<String>) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    let blacklist = database.retrieve_bool("guild_settings", "blacklist", "id", &guild_id).await;
+    database.update_bool("guild_settings", "blacklist", !blacklist, &guild_id).await;
+
+    embeds::log(ctx, &guild_id, LogEntry::Setting {
+        moderator,
+        change: SettingChange::Blacklist(!blacklist),
+    }).await;
+
+    if blacklist {
+        (Setting::WhitelistMode, vec![])
+    } else {
+        (Setting::BlacklistMode, vec![])
+    }
+}
@@ -0,0 +1,244 @@
+use serenity::{
+    client::Context,
+    model::{
+        id::{ChannelId, GuildId},
+        permissions::Permissions,
+        interactions::application_command::{
+            ApplicationCommand,
+            ApplicationCommandInteraction,
+            ApplicationCommandInteractionDataOption,
+            ApplicationCommandInteractionDataOptionValue,
+            ApplicationCommandOptionType,
+        },
+    },
+};
+
+use crate::util::{
+    embeds::{self, Meta},
+    locale::{tr, guild_locale},
+    check::{mute, Penalty, MAX_MUTE_DURATION},
+    misc::string_to_seconds,
+};
+
+use super::{
+    settings::{change_prefix, toggle_whitelist, toggle_global, toggle_mode, change_warnings, ensure_channel_table, to_strs},
+    set_streak::apply_streak,
+};
+
+// Slash command equivalents of the prefix commands, sharing the same logic
+pub async fn register(ctx: &Context) {
+    let result = ApplicationCommand::set_global_application_commands(&ctx.http, |commands| {
+        commands.create_application_command(|c| {
+            c.name("ping").description("Pong!")
+        });
+        commands.create_application_command(|c| {
+            c.name("help").description("Show a list of commands.")
+        });
+        commands.create_application_command(|c| {
+            c.name("mute").description("Manually stunlock a user.");
+            c.create_option(|o| {
+                o.name("user").description("The user to stunlock.").kind(ApplicationCommandOptionType::User).required(true)
+            });
+            c.create_option(|o| {
+                o.name("duration").description("How long to stunlock for, e.g. 30m. Follows the streak curve if omitted.").kind(ApplicationCommandOptionType::String)
+            });
+            c.create_option(|o| {
+                o.name("reason").description("Why the user is being stunlocked.").kind(ApplicationCommandOptionType::String)
+            })
+        });
+        commands.create_application_command(|c| {
+            c.name("streak").description("Set a user's streak.");
+            c.create_option(|o| {
+                o.name("user").description("The user whose streak to set.").kind(ApplicationCommandOptionType::User).required(true)
+            });
+            c.create_option(|o| {
+                o.name("streak").description("The new streak, between 0 and 16.").kind(ApplicationCommandOptionType::Integer).required(true)
+            })
+        });
+        commands.create_application_command(|c| {
+            c.name("settings").description("Change how the bot behaves in this server.");
+            c.create_option(|o| {
+                o.name("prefix").description("Show or change the command prefix.").kind(ApplicationCommandOptionType::SubCommand);
+                o.create_sub_option(|so| {
+                    so.name("prefix").description("The new prefix.").kind(ApplicationCommandOptionType::String)
+                })
+            });
+            c.create_option(|o| {
+                o.name("whitelist").description("Show the channel list or add and remove a channel or category from it.").kind(ApplicationCommandOptionType::SubCommand);
+                o.create_sub_option(|so| {
+                    so.name("channel").description("The channel to add or remove.").kind(ApplicationCommandOptionType::Channel)
+                })
+            });
+            c.create_option(|o| {
+                o.name("global").description("Toggle use of the cross-server dataset.").kind(ApplicationCommandOptionType::SubCommand)
+            });
+            c.create_option(|o| {
+                o.name("mode").description("Toggle whether the channel list is a whitelist or a blacklist.").kind(ApplicationCommandOptionType::SubCommand)
+            });
+            c.create_option(|o| {
+                o.name("warnings").description("Show or change how many warnings users get before stunlocks.").kind(ApplicationCommandOptionType::SubCommand);
+                o.create_sub_option(|so| {
+                    so.name("warnings").description("The amount of warnings.").kind(ApplicationCommandOptionType::Integer)
+                })
+            })
+        })
+    }).await;
+
+    if let Err(why) = result {
+        println!("Unable to register slash commands: {:?}", why);
+    }
+}
+
+pub async fn handle(ctx: &Context, command: &ApplicationCommandInteraction) {
+    let locale = guild_locale(ctx, command.guild_id.map(|g| g.0)).await;
+    match command.data.name.as_str() {
+        "ping" => embeds::respond(ctx, command, embeds::meta_embed(Meta::Ping, &[]), false).await,
+        "help" => embeds::respond(ctx, command, embeds::help_embed(&locale), false).await,
+        "mute" => mute_command(ctx, command, &locale).await,
+        "streak" => streak_command(ctx, command, &locale).await,
+        "settings" => settings_command(ctx, command, &locale).await,
+        _ => error(ctx, command, &locale, "error-unknown-command").await,
+    }
+}
+
+async fn error(ctx: &Context, command: &ApplicationCommandInteraction, locale: &str, key: &str) {
+    embeds::respond(ctx, command, embeds::interaction_error_embed(&tr(locale, key, &[])), true).await
+}
+
+// Returns the guild the command was used in, as long as the user has the required permissions
+async fn authorize(ctx: &Context, command: &ApplicationCommandInteraction, locale: &str, required: Permissions) -> Option<GuildId> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => {
+            error(ctx, command, locale, "error-guild-only").await;
+            return None;
+        }
+    };
+
+    let permitted = match &command.member {
+        Some(member) => match member.permissions(ctx).await {
+            Ok(p) => p.contains(required),
+            Err(_) => false,
+        },
+        None => false,
+    };
+
+    if !permitted {
+        error(ctx, command, locale, "error-no-permission").await;
+        return None;
+    }
+
+    Some(guild_id)
+}
+
+fn option<'a>(options: &'a [ApplicationCommandInteractionDataOption], name: &str) -> Option<&'a ApplicationCommandInteractionDataOptionValue> {
+    options.iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+}
+
+async fn mute_command(ctx: &Context, command: &ApplicationCommandInteraction, locale: &str) {
+    let guild_id = match authorize(ctx, command, locale, Permissions::MANAGE_MESSAGES).await {
+        Some(id) => id.0,
+        None => return,
+    };
+
+    let options = &command.data.options;
+    let offender = match option(options, "user") {
+        Some(ApplicationCommandInteractionDataOptionValue::User(user, _)) => user.clone(),
+        _ => return error(ctx, command, locale, "error-specify-user").await,
+    };
+
+    let duration = match option(options, "duration") {
+        Some(ApplicationCommandInteractionDataOptionValue::String(d)) => match string_to_seconds(d) {
+            Some(d) => Some(d.min(MAX_MUTE_DURATION)),
+            None => return error(ctx, command, locale, "error-malformed-duration").await,
+        },
+        _ => None,
+    };
+
+    let reason = match option(options, "reason") {
+        Some(ApplicationCommandInteractionDataOptionValue::String(r)) => Some(r.clone()),
+        _ => None,
+    };
+
+    embeds::respond(ctx, command, embeds::manual_mute_embed(locale, &offender, duration, &reason), false).await;
+
+    let penalty = Penalty::Manual {
+        moderator: command.user.id.0,
+        duration,
+        reason,
+    };
+
+    mute(ctx, guild_id, command.channel_id, &offender, &penalty).await;
+}
+
+async fn streak_command(ctx: &Context, command: &ApplicationCommandInteraction, locale: &str) {
+    let guild_id = match authorize(ctx, command, locale, Permissions::MANAGE_MESSAGES).await {
+        Some(id) => id.0,
+        None => return,
+    };
+
+    let options = &command.data.options;
+    let user_id = match option(options, "user") {
+        Some(ApplicationCommandInteractionDataOptionValue::User(user, _)) => user.id.0,
+        _ => return error(ctx, command, locale, "error-specify-user").await,
+    };
+
+    let streak = match option(options, "streak") {
+        Some(ApplicationCommandInteractionDataOptionValue::Integer(i)) if *i >= 0 => *i as u64,
+        _ => return embeds::respond(ctx, command, embeds::streak_bad_size_embed(locale), true).await,
+    };
+
+    if apply_streak(ctx, guild_id, command.channel_id.0, command.user.id.0, user_id, streak).await {
+        embeds::respond(ctx, command, embeds::manual_streak_embed(locale, &user_id, &streak), false).await;
+    } else {
+        embeds::respond(ctx, command, embeds::streak_bad_size_embed(locale), true).await;
+    }
+}
+
+async fn settings_command(ctx: &Context, command: &ApplicationCommandInteraction, locale: &str) {
+    let guild_id = match authorize(ctx, command, locale, Permissions::MANAGE_GUILD).await {
+        Some(id) => id.0,
+        None => return,
+    };
+    let moderator = command.user.id.0;
+
+    // The setting is the subcommand, with its arguments nested inside of it
+    let subcommand = match command.data.options.first() {
+        Some(s) => s,
+        None => return error(ctx, command, locale, "error-specify-setting").await,
+    };
+    let options = &subcommand.options;
+
+    let (setting, values) = match subcommand.name.as_str() {
+        "prefix" => {
+            let prefix = match option(options, "prefix") {
+                Some(ApplicationCommandInteractionDataOptionValue::String(p)) => Some(p.clone()),
+                _ => None,
+            };
+            change_prefix(ctx, guild_id, moderator, prefix).await
+        }
+        "whitelist" => match option(options, "channel") {
+            Some(ApplicationCommandInteractionDataOptionValue::Channel(c)) => toggle_whitelist(ctx, guild_id, moderator, ChannelId(c.id.0)).await,
+            _ => {
+                ensure_channel_table(ctx, guild_id).await;
+                let embed = embeds::whitelisted_embed(ctx, &guild_id).await;
+                return embeds::respond(ctx, command, embed, false).await;
+            }
+        },
+        "global" => toggle_global(ctx, guild_id, moderator).await,
+        "mode" => toggle_mode(ctx, guild_id, moderator).await,
+        "warnings" => {
+            let warnings = match option(options, "warnings") {
+                Some(ApplicationCommandInteractionDataOptionValue::Integer(i)) if *i >= 0 => Some(*i as u64),
+                Some(_) => return embeds::respond(ctx, command, embeds::setting_embed(locale, &embeds::Setting::Malformed, &[]), true).await,
+                None => None,
+            };
+            change_warnings(ctx, guild_id, moderator, warnings).await
+        }
+        _ => return error(ctx, command, locale, "error-unknown-setting").await,
+    };
+
+    embeds::respond(ctx, command, embeds::setting_embed(locale, &setting, &to_strs(&values)), setting.is_error()).await;
+}
@@ -0,0 +1,81 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::{
+    framework::standard::{
+        macros::command,
+        CommandResult
+    },
+    client::Context,
+    model::channel::Message,
+};
+
+use crate::{
+    util::{
+        embeds::{self, GuildStats},
+        stats::{self, ChannelStats},
+        check::enforced_channels,
+    },
+    Database, MuteCache,
+};
+
+#[command]
+#[only_in(guilds)]
+#[aliases(statistics)]
+async fn stats(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(gid) => gid.0,
+        None => return Ok(()),
+    };
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    // Totals include channels that are no longer enforced, or only recorded through the global dataset
+    let channels = stats::channels(database, guild_id).await;
+    let mut total = ChannelStats::default();
+    for c in &channels {
+        total.checked += c.checked;
+        total.recorded += c.recorded;
+        total.violations += c.violations;
+        total.mutes += c.mutes;
+        total.dm_failures += c.dm_failures;
+    }
+
+    let enforced: Vec<u64> = enforced_channels(ctx, database, guild_id).await
+        .iter()
+        .map(|c| c.id.0)
+        .collect();
+    let channels: Vec<ChannelStats> = channels.into_iter()
+        .filter(|c| enforced.contains(&c.channel))
+        .collect();
+
+    let top_offenders = stats::top_offenders(database, guild_id, 5).await;
+
+    // Mute state lives in the cache
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
+    let mute_cache = mute_arc.read().await;
+    let mut muted = 0;
+    let mut longest_streak: Option<(u64, u64)> = None;
+    if let Some(guild_data) = mute_cache.get(&guild_id) {
+        for (uid, info) in guild_data {
+            if info.mute_until > now && info.mute_until != i64::MAX as u64 {
+                muted += 1;
+            }
+            if info.streak > 0 && longest_streak.is_none_or(|(_, s)| info.streak > s) {
+                longest_streak = Some((*uid, info.streak));
+            }
+        }
+    }
+    drop(mute_cache);
+
+    embeds::stats(ctx, msg, GuildStats {
+        total,
+        channels,
+        muted,
+        longest_streak,
+        top_offenders,
+    }).await;
+
+    Ok(())
+}
@@ -1 +1,3 @@
 pub mod on_message;
+pub mod on_delete;
+pub mod on_reaction;
\ No newline at end of file
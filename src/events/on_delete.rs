@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use serenity::{
+    model::id::{ChannelId, GuildId, MessageId},
+    prelude::*,
+};
+
+use crate::{
+    util::messages,
+    Database,
+};
+
+// Audit log action type for a moderator deleting someone else's message
+const MESSAGE_DELETE_ACTION: u8 = 72;
+
+lazy_static! {
+    // The deletion count of each guild's latest audit log entries, as of the last time they were looked at
+    static ref DELETE_COUNTS: Mutex<HashMap<u64, HashMap<u64, u64>>> = Mutex::new(HashMap::new());
+}
+
+// When a message is deleted
+pub async fn message_delete(ctx: Context, channel_id: ChannelId, message_id: MessageId, guild_id: Option<GuildId>) {
+    let guild_id = match guild_id {
+        Some(id) => id.0,
+        None => return,
+    };
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    // Only messages which recorded a hash while the guild was retracting have a mapping
+    let hashes = messages::take(database, message_id.0).await;
+    let recorded = match hashes.first() {
+        Some(r) => r,
+        None => return,
+    };
+
+    let window = database.retrieve_int("guild_settings", "retract_window", "id", &guild_id).await as u64;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    if window == 0 || now.saturating_sub(recorded.time) > window {
+        return;
+    }
+
+    if deleted_by_moderator(&ctx, GuildId(recorded.guild), channel_id, recorded.user, recorded.time).await {
+        return;
+    }
+
+    for r in &hashes {
+        messages::retract(database, r.hash).await;
+    }
+}
+
+// Discord doesn't say who deleted a message, but deletions by anyone other than the author show up in the audit log
+// Repeated deletions by the same moderator in the same channel are merged into one entry, which keeps its original time and counts up instead,
+// so an older entry counts if its count went up since it was last seen, or if it hasn't been seen before
+// If the audit log can't be read the author is given the benefit of the doubt
+async fn deleted_by_moderator(ctx: &Context, guild_id: GuildId, channel_id: ChannelId, author_id: u64, since: u64) -> bool {
+    let logs = match guild_id.audit_logs(&ctx.http, Some(MESSAGE_DELETE_ACTION), None, None, Some(10)).await {
+        Ok(l) => l,
+        Err(_) => return false,
+    };
+
+    let counts: HashMap<u64, u64> = logs.entries.values()
+        .map(|entry| (entry.id.0, entry.options.as_ref().and_then(|o| o.count).unwrap_or(1)))
+        .collect();
+    let previous = DELETE_COUNTS.lock().unwrap().insert(guild_id.0, counts.clone()).unwrap_or_default();
+
+    logs.entries.values().any(|entry| {
+        if entry.target_id != Some(author_id) || entry.options.as_ref().and_then(|o| o.channel_id) != Some(channel_id) {
+            return false;
+        }
+        is_new_deletion(entry.id.created_at().timestamp() as u64, counts[&entry.id.0], previous.get(&entry.id.0).copied(), since)
+    })
+}
+
+// Whether a matching audit log entry records a deletion made after the message was sent
+fn is_new_deletion(created: u64, count: u64, previous: Option<u64>, since: u64) -> bool {
+    if created >= since {
+        return true;
+    }
+    match previous {
+        Some(previous) => count > previous,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_created_after_the_message_are_deletions() {
+        assert!(is_new_deletion(200, 1, None, 100));
+        assert!(is_new_deletion(200, 1, Some(1), 100));
+    }
+
+    #[test]
+    fn merged_entries_count_when_their_count_goes_up() {
+        assert!(is_new_deletion(50, 3, Some(2), 100));
+        assert!(!is_new_deletion(50, 2, Some(2), 100));
+    }
+
+    #[test]
+    fn unseen_older_entries_are_assumed_to_be_deletions() {
+        assert!(is_new_deletion(50, 2, None, 100));
+    }
+}
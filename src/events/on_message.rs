@@ -1,16 +1,26 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serenity::{
     model::{
-        channel::Message,
+        channel::{Message, GuildChannel},
         event::MessageUpdateEvent,
-        id::{ChannelId, MessageId},
+        id::{ChannelId, MessageId, GuildId},
         user::User,
     },
     prelude::*,
 };
 
 use crate::{
-    util::misc::{to_string, self}, 
-    check::mute,
+    util::{
+        misc::{to_string, self, Normalization},
+        database::DatabaseTool,
+        stats::{self, Counter, UserCounter},
+        guild_settings::{self, GuildSettings},
+        messages,
+        links,
+        metrics,
+    },
+    check::{mute, is_enforced, locate, Penalty},
     Database, Salt,
 };
 
@@ -20,6 +30,9 @@ pub struct FauxMessage {
     pub id: MessageId,
     pub channel_id: ChannelId,
     pub guild_id: u64,
+    // Content of the message being replied to, if any
+    pub reference: Option<String>,
+    pub stickers: Vec<u64>,
 }
 
 // When a message is sent
@@ -28,16 +41,24 @@ pub async fn message(ctx: Context, msg: Message) {
         Some(id) => id.0,
         None => return, // If the message was sent in a DM don't do anything
     };
+    if msg.author.bot {
+        return;
+    }
 
+    let fingerprinter = Fingerprinter::new(&ctx, guild_id, msg.channel_id).await;
     check(
         ctx,
         &FauxMessage {
+            reference: msg.referenced_message.map(|r| r.content),
+            stickers: msg.stickers.iter().map(|s| s.id.0).collect(),
             content: msg.content,
             author: msg.author,
             id: msg.id,
             channel_id: msg.channel_id,
             guild_id: guild_id,
         },
+        &fingerprinter,
+        false,
     )
     .await;
 }
@@ -52,6 +73,8 @@ pub async fn message_update(ctx: Context, old: Option<Message>, new: Option<Mess
                 Some(id) => id.0,
                 None => return, // If the message was sent in a DM don't do anything
             },
+            reference: m.referenced_message.map(|r| r.content),
+            stickers: m.stickers.iter().map(|s| s.id.0).collect(),
             content: m.content,
             author: m.author,
             id: m.id,
@@ -66,73 +89,323 @@ pub async fn message_update(ctx: Context, old: Option<Message>, new: Option<Mess
                 author: a,
                 id: i,
                 channel_id: event.channel_id,
+                // The raw event doesn't carry the replied to message
+                reference: None,
+                // Stickers can't be edited
+                stickers: Vec::new(),
             }
         } else {
             return;
         }
     }
 
+    if msg.author.bot {
+        return;
+    }
+
+    let fingerprinter = Fingerprinter::new(&ctx, msg.guild_id, msg.channel_id).await;
+
     // If the message was updated and nothing was changed then don't do anything
-    if let Some(o) = old {
+    if let Some(o) = &old {
         // Compare to the hashes of the strings because the hash function trims puntuation and whitespace
-        if misc::hash(&o.content) == misc::hash(&msg.content) {
+        // The old content is hashed the same way the new content will be, so the comparison lines up
+        if fingerprinter.fingerprint(&o.content, msg.reference.as_deref(), &msg.stickers) == fingerprinter.fingerprint(&msg.content, msg.reference.as_deref(), &msg.stickers) {
             return;
         }
     }
 
-    check(ctx, &msg).await;
+    {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+        match fingerprinter.settings.edit_policy {
+            EditPolicy::Check => {},
+            EditPolicy::Ignore => return,
+            EditPolicy::Grace => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                let sent = msg.id.created_at().timestamp() as u64;
+                if now.saturating_sub(sent) > fingerprinter.settings.edit_window {
+                    return;
+                }
+            }
+            EditPolicy::Replace => {
+                // Only hashes this message is known to have recorded are retracted
+                // The same content could have been recorded by an earlier message, which keeps it in the dataset
+                for record in messages::take(database, msg.id.0).await {
+                    messages::retract(database, record.hash).await;
+                }
+            }
+        }
+    }
+
+    check(ctx, &msg, &fingerprinter, true).await;
+}
+
+// How long a message's recorded hashes are remembered in replace mode, later edits are checked without retracting anything
+const REPLACE_WINDOW: u64 = 60 * 60 * 24 * 7;
+
+// Longest grace window a guild can give edits
+pub const MAX_EDIT_WINDOW: u64 = 60 * 60 * 24 * 7;
+
+pub enum EditPolicy {
+    // Every edit is checked like a new message
+    Check,
+    // Edits are never checked
+    Ignore,
+    // Edits are only checked within a window after the message was sent
+    Grace,
+    // The old content's hash is retracted and the new content is checked in its place
+    Replace,
 }
 
-async fn check(ctx: Context, msg: &FauxMessage) {
+impl From<i64> for EditPolicy {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => EditPolicy::Ignore,
+            2 => EditPolicy::Grace,
+            3 => EditPolicy::Replace,
+            _ => EditPolicy::Check,
+        }
+    }
+}
+
+impl EditPolicy {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            EditPolicy::Check => 0,
+            EditPolicy::Ignore => 1,
+            EditPolicy::Grace => 2,
+            EditPolicy::Replace => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EditPolicy::Check => "check",
+            EditPolicy::Ignore => "ignore",
+            EditPolicy::Grace => "grace",
+            EditPolicy::Replace => "replace",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "check" => Some(EditPolicy::Check),
+            "ignore" => Some(EditPolicy::Ignore),
+            "grace" => Some(EditPolicy::Grace),
+            "replace" => Some(EditPolicy::Replace),
+            _ => None,
+        }
+    }
+}
+
+// Edits were already counted when the message was sent, so only the violation they cause adds to the statistics
+async fn check(ctx: Context, msg: &FauxMessage, fingerprinter: &Fingerprinter, edited: bool) {
     if msg.author.bot {
         return;
     }
 
+    let guild_id = msg.guild_id;
+    let (whitelisted, hash) = match fingerprinter.fingerprint(&msg.content, msg.reference.as_deref(), &msg.stickers) {
+        Some(f) => f,
+        None => return,
+    };
+
+    let link_hashes = fingerprinter.links(&msg.content);
+
+    // Statistics for threads are counted towards their parent channel
+    let stats_channel = fingerprinter.namespace.0;
+
     let data = ctx.data.read().await;
     let database = data.get::<Database>().expect("Expected Database in TypeMap");
 
-    let guild_id = msg.guild_id;
-    let channel_table = &format!("channels_{}", guild_id);
-    
-    let whitelisted: bool;
-    if database.table_exists(channel_table).await {
-        whitelisted = database.row_exists(channel_table, "id", &msg.channel_id.0).await;
-    } else {
-        whitelisted = false;
-    }
-
-    // If the server isn't using the global dataset, then salt the message with the salt along with the guild's and the channel's id
-    let hash: u128;
-    if database.retrieve_bool("guild_settings", "global", "id", &guild_id).await {
-        // Global dataset enabled
-        hash = misc::hash(&msg.content);
-   } else {
-        // Global dataset disabled
-        if whitelisted {
-            let salt = data.get::<Salt>().expect("Expected Salt in TypeMap");
-            hash = misc::hash(&format!("{}{}{}{}", salt, msg.content, guild_id, msg.channel_id.0));
+    // Exempt users are never punished, and only contribute to the dataset if the guild wants them to
+    let exempt = is_exempt(&ctx, database, guild_id, msg.author.id.0).await;
+    let settings = &fingerprinter.settings;
+    let record = !exempt || settings.record_exempt;
+
+    if !edited {
+        stats::increment(database, guild_id, stats_channel, Counter::Checked).await;
+    }
+    metrics::message_checked();
+
+    let infringing = database.row_exists("global", "id", &hash).await;
+    metrics::hash_lookup(infringing);
+
+    // A reposted link is a violation on its own, even if the text around it is original
+    let mut reposted = None;
+    let mut new_links = Vec::new();
+    for link_hash in link_hashes {
+        if database.row_exists("global", "id", &link_hash).await {
+            reposted = reposted.or(Some(link_hash));
         } else {
-            // If the channel isn't whitelisted there's no point in storing anything
-            return;
+            new_links.push(link_hash);
         }
     }
+    let violating = whitelisted && !exempt && (infringing || reposted.is_some());
 
-    let infringing = database.row_exists("global", "id", &hash).await;
-    if !infringing {
-        database.insert_row("global", &[&to_string(hash)]).await
+    // Nothing from a message that's about to be deleted is recorded
+    if record && !violating {
+        let mut recorded = new_links;
+        if !infringing {
+            recorded.insert(0, hash);
+        }
+
+        // Remember which hashes this message recorded, in case its author deletes it shortly after or edits it in replace mode
+        let mapping_window = match settings.edit_policy {
+            EditPolicy::Replace => settings.retract_window.max(REPLACE_WINDOW),
+            _ => settings.retract_window,
+        };
+        for recorded_hash in &recorded {
+            database.insert_row("global", &[&to_string(recorded_hash)]).await;
+
+            if mapping_window != 0 {
+                messages::record(database, msg.id.0, guild_id, msg.author.id.0, *recorded_hash, mapping_window).await;
+            }
+        }
+        if !recorded.is_empty() && !edited {
+            stats::increment(database, guild_id, stats_channel, Counter::Recorded).await;
+        }
     }
-    drop(database);
     
-    if whitelisted && infringing{
+    if whitelisted && !infringing && reposted.is_none() && !edited {
+        stats::increment_user(database, guild_id, msg.author.id.0, UserCounter::Original).await;
+    }
+    
+    if violating {
+        stats::increment(database, guild_id, stats_channel, Counter::Violations).await;
+        stats::increment_user(database, guild_id, msg.author.id.0, UserCounter::Violations).await;
+        drop(data);
         delete_message(&ctx, msg).await;
-        mute(&ctx, msg).await;
+        let hash = if infringing { hash } else { reposted.unwrap_or(hash) };
+        mute(&ctx, guild_id, msg.channel_id, &msg.author, &Penalty::Automatic { hash, content: msg.content.clone() }).await;
+    }
+}
+
+// Everything hashing a message depends on, looked up once per message rather than once per hash
+pub struct Fingerprinter {
+    pub settings: GuildSettings,
+    // Whether r9k is enforced in the channel
+    pub whitelisted: bool,
+    // Messages in threads share the namespace of the parent channel
+    pub namespace: ChannelId,
+    salt: String,
+}
+
+impl Fingerprinter {
+    pub async fn new(ctx: &Context, guild_id: u64, channel_id: ChannelId) -> Self {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+        let settings = guild_settings::load(database, guild_id).await;
+        let location = locate(ctx, channel_id).await;
+        let whitelisted = is_enforced(database, &settings, &location).await;
+        let namespace = location.parent;
+        let salt = data.get::<Salt>().expect("Expected Salt in TypeMap").clone();
+
+        Fingerprinter { settings, whitelisted, namespace, salt }
+    }
+
+    // Returns whether r9k is enforced in the channel along with the hash a message sent there would be recorded as
+    // Returns None if the message wouldn't be recorded at all
+    // Replies are only distinguished by what they reply to if the guild opted into it
+    // Messages without any text are hashed by their custom emoji and stickers instead, if the guild opted into it
+    pub fn fingerprint(&self, content: &str, reference: Option<&str>, stickers: &[u64]) -> Option<(bool, u128)> {
+        let settings = &self.settings;
+
+        // If the channel isn't whitelisted and the guild has its own dataset there's no point in storing anything
+        if !settings.global && !self.whitelisted {
+            return None;
+        }
+
+        let mut normalized = misc::normalize(content, &settings.normalization);
+        if normalized.is_empty() && settings.emoji_fingerprints {
+            let stream = misc::emoji_stream(content, stickers);
+            if !stream.is_empty() {
+                normalized = misc::normalize(&stream, &Normalization::default());
+            }
+        }
+
+        let mut hash = namespaced(&self.salt, settings.global, settings.guild_id, self.namespace, &normalized);
+
+        // Mixing in the replied to message's hash makes the same reply to a different message original
+        if let Some(r) = reference {
+            if !r.is_empty() && settings.reply_context {
+                hash = misc::hash(&format!("{}reply{}", hash, misc::hash_with(r, &settings.normalization)));
+            }
+        }
+
+        Some((self.whitelisted, hash))
+    }
+
+    // Hashes of every link in a message, kept apart from the text so reposted links are caught regardless of what's said around them
+    // Empty unless the guild opted into it, or if nothing would be recorded
+    pub fn links(&self, content: &str) -> Vec<u128> {
+        let settings = &self.settings;
+        if !settings.link_fingerprints || (!settings.global && !self.whitelisted) {
+            return Vec::new();
+        }
+
+        links::extract(content).iter()
+            .map(|l| namespaced(&self.salt, settings.global, settings.guild_id, self.namespace, &format!("link:{}", l)))
+            .collect()
+    }
+}
+
+// Without the global dataset, hashes are salted with the salt along with the guild's and the channel's id
+// The content is normalized on its own beforehand, so the salt can't run into a leading quote or code block
+fn namespaced(salt: &str, global: bool, guild_id: u64, channel_id: ChannelId, normalized: &str) -> u128 {
+    if global {
+        misc::hash_normalized(normalized)
+    } else {
+        let salt = misc::normalize(salt, &Normalization::default());
+        misc::hash_normalized(&format!("{}{}{}{}", salt, normalized, guild_id, channel_id.0))
+    }
+}
+
+pub async fn is_exempt(ctx: &Context, database: &DatabaseTool, guild_id: u64, user_id: u64) -> bool {
+    let user_table = &format!("exempt_users_{}", guild_id);
+    if database.table_exists(user_table).await && database.row_exists(user_table, "id", &user_id).await {
+        return true;
+    }
+
+    let role_table = &format!("exempt_roles_{}", guild_id);
+    if !database.table_exists(role_table).await {
+        return false;
+    }
+
+    let exempt_roles = database.get_all_rows(role_table, "id").await;
+    if exempt_roles.is_empty() {
+        return false;
+    }
+
+    match GuildId(guild_id).member(ctx, user_id).await {
+        Ok(member) => member.roles.iter().any(|r| exempt_roles.contains(&r.0)),
+        Err(_) => false,
     }
 }
 
 async fn delete_message(ctx: &Context, msg: &FauxMessage) {
     if let Ok(c) = msg.channel_id.to_channel(&ctx.http).await {
         if let Some(gc) = c.guild() {
-            let _ = gc.delete_messages(&ctx.http, &[msg.id]).await;
+            if gc.delete_messages(&ctx.http, &[msg.id]).await.is_err() {
+                metrics::http_error("delete_message");
+            }
+        }
+    }
+}
+
+// When a thread is created
+pub async fn thread_create(ctx: Context, thread: GuildChannel) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let settings = guild_settings::load(database, thread.guild_id.0).await;
+
+    // Bots only receive messages from threads they've joined
+    if is_enforced(database, &settings, &locate(&ctx, thread.id).await).await {
+        if let Err(why) = thread.id.join_thread(&ctx.http).await {
+            println!("Unable to join thread [thread_create] Why: {:?}", why);
+            metrics::http_error("join_thread");
         }
     }
 }
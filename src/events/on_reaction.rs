@@ -0,0 +1,74 @@
+use serenity::{
+    model::channel::{Reaction, ReactionType},
+    prelude::*,
+};
+
+use crate::{
+    events::on_message::is_exempt,
+    util::{
+        reactions::{self, ReactionMode},
+        guild_settings,
+        stats::{self, Counter},
+        metrics,
+    },
+    check::{mute, is_enforced, locate, Penalty},
+    Database,
+};
+
+// When a reaction is added
+pub async fn reaction_add(ctx: Context, reaction: Reaction) {
+    let guild_id = match reaction.guild_id {
+        Some(id) => id.0,
+        None => return,
+    };
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    // Checked first, most guilds leave reactions alone and shouldn't cost a request per reaction
+    let settings = guild_settings::load(database, guild_id).await;
+    if let ReactionMode::Off = settings.reaction_mode {
+        return;
+    }
+
+    let user = match reaction.user(&ctx).await {
+        Ok(u) => u,
+        Err(_) => return,
+    };
+    if user.bot {
+        return;
+    }
+
+    let location = locate(&ctx, reaction.channel_id).await;
+    if !is_enforced(database, &settings, &location).await {
+        return;
+    }
+
+    // Custom emojis are keyed by id, so renaming them doesn't make them original again
+    let emoji = match &reaction.emoji {
+        ReactionType::Custom { id, .. } => format!("custom:{}", id.0),
+        ReactionType::Unicode(s) => s.clone(),
+        _ => return,
+    };
+
+    // Threads share the namespace of their parent channel
+    let channel_id = location.parent.0;
+
+    if !reactions::is_repeat(database, &settings, channel_id, reaction.message_id.0, &emoji, user.id.0).await {
+        reactions::record(database, guild_id, channel_id, reaction.message_id.0, &emoji, user.id.0, settings.reaction_window).await;
+        return;
+    }
+
+    if is_exempt(&ctx, database, guild_id, user.id.0).await {
+        return;
+    }
+
+    stats::increment(database, guild_id, channel_id, Counter::Violations).await;
+    drop(data);
+
+    if let Err(why) = reaction.delete(&ctx).await {
+        println!("Unable to remove reaction [reaction_add] Why: {:?}", why);
+        metrics::http_error("delete_reaction");
+    }
+    mute(&ctx, guild_id, reaction.channel_id, &user, &Penalty::Reaction { emoji: reaction.emoji.to_string() }).await;
+}
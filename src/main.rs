@@ -7,6 +7,12 @@ use commands::{
     settings::*,
     mute::*,
     set_streak::*,
+    history::*,
+    originality::*,
+    stats::*,
+    leaderboard::*,
+    backfill::*,
+    dms::*,
 };
 
 use std::{
@@ -21,7 +27,6 @@ use std::{
         HashSet,
         HashMap
     },
-    env,
     time::Duration
 };
 
@@ -29,17 +34,22 @@ use serenity::{
     async_trait,
     client::bridge::gateway::ShardManager,
     framework::{
-        standard::macros::{
+        standard::{
+            macros::{
                 group,
                 hook,
             },
+            DispatchError,
+        },
         StandardFramework
     },
     http::Http,
     model::{
-        event::{ResumedEvent, MessageUpdateEvent},
+        event::{Event, ResumedEvent, MessageUpdateEvent},
         gateway::Ready,
-        channel::Message, id::GuildId,
+        channel::{Message, GuildChannel, Reaction}, id::{GuildId, ChannelId, MessageId},
+        guild::Guild,
+        interactions::Interaction,
     },
     prelude::*,
 };
@@ -54,37 +64,51 @@ use util::{
 
 use crate::util::{
     database::{
+        ColumnType,
         INTEGER,
         TEXT,
         BOOL,
+        SHORT_TEXT,
     }, 
-    misc::to_string, check,
+    check, history, embeds, stats, backfill, messages, reactions, locale, template, dms, metrics,
+    config::Config,
 };
 
 #[group]
 #[commands(
-    ping, settings, help, mute_command, set_streak
+    ping, settings, help, mute_command, set_streak, history, originality, stats,
+    leaderboard, profile, backfill, dms
 )]
 struct General;
 
 struct Handler {
     is_loop_running: AtomicBool,
+    loop_interval: Duration,
 }
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         info!("Connected as {}", ready.user.name);
+        commands::slash::register(&ctx).await;
     }
 
     async fn cache_ready(&self, ctx: Context, _: Vec<GuildId>) {
         let ctx = Arc::new(ctx);
         if !self.is_loop_running.load(Ordering::Relaxed) {
             let ctx1 = Arc::clone(&ctx);
+            let loop_interval = self.loop_interval;
             tokio::spawn(async move {
                 loop {
+                    let timer = metrics::loop_timer();
                     check::check_loop(Arc::clone(&ctx1)).await;
-                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    {
+                        let data = ctx1.data.read().await;
+                        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+                        messages::prune(database).await;
+                    }
+                    timer.observe_duration();
+                    tokio::time::sleep(loop_interval).await;
                 }
             });
 
@@ -111,6 +135,13 @@ impl EventHandler for Handler {
         println!("Began looping through the mute cache");
     }
 
+    // Sent for every guild on startup and whenever the bot joins one, so settings exist before anything reads them
+    async fn guild_create(&self, ctx: Context, guild: Guild, _: bool) {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        database.ensure_guild(&guild.id.0).await;
+    }
+
     async fn resume(&self, _: Context, _: ResumedEvent) {
         info!("Resumed");
     }
@@ -122,6 +153,24 @@ impl EventHandler for Handler {
     async fn message_update(&self, ctx: Context, old: Option<Message>, new: Option<Message>, event: MessageUpdateEvent) {
         events::on_message::message_update(ctx, old, new, event).await;
     }
+
+    async fn message_delete(&self, ctx: Context, channel_id: ChannelId, deleted_message_id: MessageId, guild_id: Option<GuildId>) {
+        events::on_delete::message_delete(ctx, channel_id, deleted_message_id, guild_id).await;
+    }
+
+    async fn thread_create(&self, ctx: Context, thread: GuildChannel) {
+        events::on_message::thread_create(ctx, thread).await;
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        events::on_reaction::reaction_add(ctx, reaction).await;
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::ApplicationCommand(command) = interaction {
+            commands::slash::handle(&ctx, &command).await;
+        }
+    }
 }
 
 pub struct ShardManagerContainer;
@@ -142,12 +191,69 @@ impl TypeMapKey for MuteCache {
     type Value = Arc<RwLock<HashMap<u64, HashMap<u64, MuteInfo>>>>;
 }
 
+// Prefix of each guild, filled in on first use so that messages don't have to query it
+pub struct PrefixCache;
+
+impl TypeMapKey for PrefixCache {
+    type Value = Arc<RwLock<HashMap<u64, String>>>;
+}
+
+pub struct Backfills;
+
+impl TypeMapKey for Backfills {
+    type Value = Arc<RwLock<HashMap<u64, Arc<AtomicBool>>>>;
+}
+
 struct Salt;
 
 impl TypeMapKey for Salt {
     type Value = String;
 }
 
+// Column name, type, and default value
+const GUILD_SETTINGS_COLUMNS: &[(&str, ColumnType, &str)] = &[
+    ("log_channel", INTEGER, "0"),
+    ("log_content", BOOL, "0"),
+    ("record_exempt", BOOL, "1"),
+    ("warnings", INTEGER, "0"),
+    ("blacklist", BOOL, "0"),
+    ("retract_window", INTEGER, "0"),
+    ("edit_policy", INTEGER, "0"),
+    ("edit_window", INTEGER, "60"),
+    ("reaction_mode", INTEGER, "0"),
+    ("reaction_window", INTEGER, "300"),
+    ("reply_context", BOOL, "0"),
+    ("code_verbatim", BOOL, "0"),
+    ("strip_spoilers", BOOL, "1"),
+    ("separate_urls", BOOL, "0"),
+    ("link_fingerprints", BOOL, "0"),
+    ("emoji_fingerprints", BOOL, "0"),
+    ("language", SHORT_TEXT, "'en'"),
+    ("notice_mode", INTEGER, "0"),
+    ("notice_lifetime", INTEGER, "10"),
+    ("direct_messages", BOOL, "1"),
+];
+
+// Sees every gateway event before the regular handler, only used to count them
+struct RawHandler;
+
+#[async_trait]
+impl RawEventHandler for RawHandler {
+    async fn raw_event(&self, _ctx: Context, event: Event) {
+        metrics::gateway_event(&format!("{:?}", event.event_type()));
+    }
+}
+
+#[hook]
+async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError) {
+    if let DispatchError::Ratelimited(info) = error {
+        // Only tell the user once, instead of every time they try again
+        if info.is_first_try {
+            embeds::rate_limited(ctx, msg, info.rate_limit.as_secs()).await;
+        }
+    }
+}
+
 #[hook]
 async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
     let guild_id = match msg.guild_id {
@@ -155,20 +261,16 @@ async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
         None => 0,
     };
 
-    let prefix: String;
-
-    if guild_id != 0 {
-        let data = ctx.data.read().await;
-        let database = data.get::<Database>().expect("Expected Database in TypeMap");
-
-        if !database.row_exists("guild_settings", "id", &guild_id).await {
-            database.insert_row("guild_settings", &[&to_string(guild_id), "9!", "1"]).await;
-        }
-        prefix = database.retrieve_str("guild_settings", "prefix", "id", &guild_id).await;
-    } else {
-        prefix = "9!".to_string();
+    let data = ctx.data.read().await;
+    let prefix_arc = data.get::<PrefixCache>().expect("Expected PrefixCache in TypeMap");
+    if let Some(prefix) = prefix_arc.read().await.get(&guild_id) {
+        return Some(prefix.clone());
     }
 
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let prefix = database.prefix(guild_id).await;
+    prefix_arc.write().await.insert(guild_id, prefix.clone());
+
     Some(prefix)
 }
 
@@ -176,7 +278,8 @@ async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
 async fn main() {
     // This will load the environment variables located at `./.env`, relative to
     // the CWD. See `./.env.example` for an example on how to structure this.
-    dotenv::dotenv().expect("Failed to load .env file");
+    // It's optional now that everything can also come from the config file
+    dotenv::dotenv().ok();
 
     // Initialize the logger to use environment variables.
     //
@@ -188,7 +291,27 @@ async fn main() {
 
     tracing::subscriber::set_global_default(subscriber).expect("Failed to start the logger");
 
-    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    // Every shipped locale must have every message English has, otherwise some embeds would silently fall back
+    let problems = locale::validate();
+    if !problems.is_empty() {
+        for problem in &problems {
+            error!("{}", problem);
+        }
+        panic!("Found {} problems in the locales", problems.len());
+    }
+
+    // Report every configuration problem at once, instead of failing on the first missing variable
+    let config = match Config::load() {
+        Ok(c) => c,
+        Err(problems) => {
+            for problem in &problems {
+                error!("{}", problem);
+            }
+            panic!("Found {} problems in the configuration", problems.len());
+        }
+    };
+
+    let token = config.discord.token;
 
     let http = Http::new_with_token(&token);
 
@@ -211,27 +334,29 @@ async fn main() {
             .on_mention(Some(bot_id))
             .dynamic_prefix(dynamic_prefix)
         })
+        .on_dispatch_error(dispatch_error)
+        // Limits how quickly users can probe the dataset with the check command
+        .bucket("originality", |b| b.delay(10).time_span(600).limit(5))
+        .await
         .group(&GENERAL_GROUP);
 
-    let salt = env::var("SALT").expect("Expected a salt in the environment");
-    
-    let host = env::var("MYSQL_HOST").expect("Expected the database host in the environment");
-    let username = env::var("MYSQL_USERNAME").expect("Expected the database username in the environment");
-    let password = env::var("MYSQL_PASSWORD").expect("Expected the database password in the environment");
-    let db = env::var("MYSQL_DB").expect("Expected the database name in the environment");
+    let salt = config.bot.salt;
+    let db = config.database;
 
     let database = DatabaseTool {
         pool: sqlx::mysql::MySqlPoolOptions::new()
-        .max_connections(5)
+        .max_connections(db.max_connections)
         .connect_with(
             sqlx::mysql::MySqlConnectOptions::new()
-                .host(&host)
-                .username(&username)
-                .password(&password)
-                .database(&db),
+                .host(&db.host)
+                .username(&db.username)
+                .password(&db.password)
+                .database(&db.name),
         )
         .await
         .expect("Coundn't connect to database"),
+        schema: db.name,
+        default_prefix: config.bot.default_prefix,
     };
 
     let mut mute_map: HashMap<u64, HashMap<u64, MuteInfo>> = HashMap::new();
@@ -240,20 +365,39 @@ async fn main() {
         database.create_table("guild_settings", &vec!["id", "prefix", "global"], &vec![INTEGER, TEXT, BOOL]).await;
     }
 
+    // Settings added after the table was first introduced, so older databases get them too
+    for (column, column_type, default) in GUILD_SETTINGS_COLUMNS {
+        if !database.column_exists("guild_settings", column).await {
+            database.add_column("guild_settings", column, *column_type, Some(default)).await;
+        }
+    }
+
     if !database.table_exists("global").await {
         database.create_table("global", &vec!["id"], &vec![TEXT]).await
     }
 
+    history::create_table(&database).await;
+    stats::create_table(&database).await;
+    stats::create_user_table(&database).await;
+    backfill::create_table(&database).await;
+    messages::create_table(&database).await;
+    reactions::create_table(&database).await;
+    template::create_table(&database).await;
+    dms::create_table(&database).await;
+
     
     for gid in database.get_all_rows("guild_settings", "id").await {
         let mut guild_data: HashMap<u64, MuteInfo> = HashMap::new();
         let stunlock_table = &format!("stunlocks_{}", gid);
         if database.table_exists(stunlock_table).await {
+            check::stunlock_table(&database, &gid).await;
             for uid in database.get_all_rows(stunlock_table, "id").await {
                 guild_data.insert(uid, MuteInfo {
                     streak: database.retrieve_int(stunlock_table, "streak", "id", &uid).await as u64,
                     streak_time: database.retrieve_int(stunlock_table, "streak_time", "id", &uid).await as u64,
                     mute_until: database.retrieve_int(stunlock_table, "mute_until", "id", &uid).await as u64, 
+                    warnings: database.retrieve_int(stunlock_table, "warnings", "id", &uid).await as u64,
+                    warning_time: database.retrieve_int(stunlock_table, "warning_time", "id", &uid).await as u64,
                 });
             }
         }
@@ -263,13 +407,16 @@ async fn main() {
     let mute_cache = Arc::new(RwLock::new(mute_map));
 
     let mut client = Client::builder(&token)
+        .application_id(bot_id.0)
         .framework(framework)
         .event_handler(Handler {
             is_loop_running: AtomicBool::new(false), 
+            loop_interval: Duration::from_secs(config.bot.loop_interval),
         })
+        .raw_event_handler(RawHandler)
         .cache_settings(|s|
             s
-            .max_messages(10000)
+            .max_messages(config.discord.message_cache)
         )
         .await
         .expect("Err creating client");
@@ -279,9 +426,15 @@ async fn main() {
         data.insert::<Salt>(salt);
         data.insert::<Database>(database);
         data.insert::<MuteCache>(mute_cache);
+        data.insert::<PrefixCache>(Arc::new(RwLock::new(HashMap::new())));
+        data.insert::<Backfills>(Arc::new(RwLock::new(HashMap::new())));
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
     }
 
+    if let Some(address) = config.metrics.address() {
+        tokio::spawn(metrics::serve(address));
+    }
+
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
     }
@@ -0,0 +1,184 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, MessageId},
+};
+
+use crate::{events::on_message::Fingerprinter, Backfills, Database};
+
+use super::{
+    database::{DatabaseTool, INTEGER, BOOL},
+    embeds::{self, BackfillStatus},
+    locale,
+    misc::to_string,
+    metrics,
+};
+
+// Messages fetched per request, the most the API allows
+const PAGE_SIZE: u64 = 100;
+
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("backfills").await {
+        // before_id is the oldest message processed so far, which is where a resumed job continues from
+        database.create_table("backfills", &["id", "guild", "before_id", "count", "done"], &[INTEGER, INTEGER, INTEGER, INTEGER, BOOL]).await;
+    }
+}
+
+// Returns false if a job is already running in the channel
+pub async fn start(ctx: Arc<Context>, guild_id: u64, channel_id: ChannelId, report_channel: ChannelId) -> bool {
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let data = ctx.data.read().await;
+        let backfills = data.get::<Backfills>().expect("Expected Backfills in TypeMap");
+        let mut running = backfills.write().await;
+        if running.contains_key(&channel_id.0) {
+            return false;
+        }
+        running.insert(channel_id.0, Arc::clone(&cancel));
+    }
+
+    tokio::spawn(async move {
+        run(&ctx, guild_id, channel_id, report_channel, &cancel).await;
+
+        let data = ctx.data.read().await;
+        let backfills = data.get::<Backfills>().expect("Expected Backfills in TypeMap");
+        backfills.write().await.remove(&channel_id.0);
+    });
+
+    true
+}
+
+// Returns false if there was no job running in the channel
+pub async fn cancel(ctx: &Context, channel_id: ChannelId) -> bool {
+    let data = ctx.data.read().await;
+    let backfills = data.get::<Backfills>().expect("Expected Backfills in TypeMap");
+    let running = backfills.read().await;
+    match running.get(&channel_id.0) {
+        Some(c) => {
+            c.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+async fn run(ctx: &Context, guild_id: u64, channel_id: ChannelId, report_channel: ChannelId, cancel: &AtomicBool) {
+    let (mut before, mut count) = progress(ctx, guild_id, channel_id).await;
+    let locale = locale::guild_locale(ctx, Some(guild_id)).await;
+
+    let mut report = match report_channel.send_message(ctx, |m| m.set_embed(embeds::backfill_embed(&locale, channel_id.0, count, BackfillStatus::Running))).await {
+        Ok(m) => Some(m),
+        Err(why) => {
+            println!("Error sending message: {:?}", why);
+            metrics::http_error("send_message");
+            None
+        }
+    };
+
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            break BackfillStatus::Cancelled;
+        }
+
+        let page = match channel_id.messages(&ctx.http, |r| {
+            if before != 0 {
+                r.before(MessageId(before));
+            }
+            r.limit(PAGE_SIZE)
+        }).await {
+            Ok(p) => p,
+            Err(why) => {
+                println!("Unable to fetch channel history [backfill] Why: {:?}", why);
+                metrics::http_error("channel_history");
+                break BackfillStatus::Failed;
+            }
+        };
+
+        if page.is_empty() {
+            break BackfillStatus::Done;
+        }
+
+        {
+            // Settings are reloaded per page, so changes made during a long backfill are picked up
+            let fingerprinter = Fingerprinter::new(ctx, guild_id, channel_id).await;
+            let data = ctx.data.read().await;
+            let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+            for msg in &page {
+                if msg.author.bot {
+                    continue;
+                }
+
+                // Hashed the same way as a live message, but never punished
+                let stickers: Vec<u64> = msg.stickers.iter().map(|s| s.id.0).collect();
+                let mut hashes = fingerprinter.links(&msg.content);
+                if let Some((_, hash)) = fingerprinter.fingerprint(&msg.content, msg.referenced_message.as_ref().map(|r| r.content.as_str()), &stickers) {
+                    hashes.push(hash);
+                }
+                for hash in hashes {
+                    if !database.row_exists("global", "id", &hash).await {
+                        database.insert_row("global", &[&to_string(hash)]).await;
+                    }
+                }
+            }
+
+            // Pages are returned newest first
+            before = page.last().unwrap().id.0;
+            count += page.len() as u64;
+            database.update_int("backfills", "before_id", &before, &channel_id.0).await;
+            database.update_int("backfills", "count", &count, &channel_id.0).await;
+        }
+
+        if let Some(r) = &mut report {
+            let _ = r.edit(ctx, |m| m.embed(|e| {
+                *e = embeds::backfill_embed(&locale, channel_id.0, count, BackfillStatus::Running);
+                e
+            })).await;
+        }
+
+        // Go easy on the rate limits
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+
+    if let BackfillStatus::Done = status {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        database.update_bool("backfills", "done", true, &channel_id.0).await;
+    }
+
+    if let Some(r) = &mut report {
+        let _ = r.edit(ctx, |m| m.embed(|e| {
+            *e = embeds::backfill_embed(&locale, channel_id.0, count, status);
+            e
+        })).await;
+    }
+}
+
+// Where to continue from, a finished job starts over from the newest message
+async fn progress(ctx: &Context, guild_id: u64, channel_id: ChannelId) -> (u64, u64) {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    if database.row_exists("backfills", "id", &channel_id.0).await {
+        if !database.retrieve_bool("backfills", "done", "id", &channel_id.0).await {
+            let before = database.retrieve_int("backfills", "before_id", "id", &channel_id.0).await as u64;
+            let count = database.retrieve_int("backfills", "count", "id", &channel_id.0).await as u64;
+            return (before, count);
+        }
+
+        database.update_int("backfills", "before_id", &0u64, &channel_id.0).await;
+        database.update_int("backfills", "count", &0u64, &channel_id.0).await;
+        database.update_bool("backfills", "done", false, &channel_id.0).await;
+    } else {
+        database.insert_row("backfills", &[&to_string(channel_id.0), &to_string(guild_id), "0", "0", "false"]).await;
+    }
+
+    (0, 0)
+}
@@ -5,53 +5,218 @@ use serenity::{
     model::{
         permissions::Permissions, 
         channel::{
+            Channel,
+            ChannelType,
+            GuildChannel,
             PermissionOverwrite,
             PermissionOverwriteType
         },
-        prelude::UserId, 
-        id::ChannelId,
+        prelude::{UserId, User},
+        id::{ChannelId, GuildId},
     }
 };
 
-use crate::{Database, MuteCache, events::on_message::FauxMessage,};
+use crate::{Database, MuteCache};
 
-use super::{embeds, misc::to_string, database::INTEGER};
+use super::{
+    embeds::{self, LogEntry},
+    misc::to_string,
+    database::{DatabaseTool, ColumnType, INTEGER, TEXT},
+    guild_settings::GuildSettings,
+    history::{self, HistoryKind, HistoryRecord},
+    stats::{self, Counter},
+    metrics,
+};
 
 pub async fn check_loop(ctx: Arc<Context>) {
     let data = ctx.data.read().await;
     let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
     let mut mute_cache = mute_arc.write().await;
     for (gid, guild_data) in &mut *mute_cache {
-        for (uid, author_data) in guild_data {
-            author_data.update(&ctx, gid, uid).await;
+        let mut expired = Vec::new();
+        for (uid, author_data) in guild_data.iter_mut() {
+            if author_data.update(&ctx, gid, uid).await {
+                expired.push(*uid);
+            }
+        }
+        for uid in expired {
+            guild_data.remove(&uid);
         }
     }
 }
 
 trait MutePermissions {
     fn mute(user_id: u64) -> Vec<Self> where Self: Sized;
-    fn unmute(user_id: u64) -> Vec<Self> where Self: Sized;
 }
 
 impl MutePermissions for PermissionOverwrite {
     fn mute(user_id: u64) -> Vec<PermissionOverwrite> where PermissionOverwrite: Sized {
         vec![PermissionOverwrite {
             allow: Permissions::empty(),
+            // Threads inherit overwrites from their parent, so this covers posting in them as well
             deny: Permissions::ADD_REACTIONS
-            .union(Permissions::SEND_MESSAGES),
+            .union(Permissions::SEND_MESSAGES)
+            .union(Permissions::SEND_MESSAGES_IN_THREADS)
+            .union(Permissions::CREATE_PUBLIC_THREADS)
+            .union(Permissions::CREATE_PRIVATE_THREADS),
             kind: PermissionOverwriteType::Member(UserId(user_id)),
         },
         ]
     }
+}
 
-    fn unmute(user_id: u64) -> Vec<PermissionOverwrite> where PermissionOverwrite: Sized {
-        vec![PermissionOverwrite {
-            allow: Permissions::empty(),
-            deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Member(UserId(user_id)),
-        },
-        ]
+// Where a channel sits in its guild, resolved once so that handlers don't have to look the channel up repeatedly
+// Threads are located by their parent channel
+pub struct ChannelLocation {
+    pub parent: ChannelId,
+    pub category: Option<ChannelId>,
+}
+
+pub async fn locate(ctx: &Context, channel_id: ChannelId) -> ChannelLocation {
+    match channel_id.to_channel(ctx).await {
+        // A thread's parent channel is deserialized into category_id, its category has to come from the parent
+        Ok(Channel::Guild(c)) if is_thread(&c) => {
+            let parent = c.category_id.unwrap_or(channel_id);
+            let category = match parent.to_channel(ctx).await {
+                Ok(Channel::Guild(p)) => p.category_id,
+                _ => None,
+            };
+            ChannelLocation { parent, category }
+        }
+        Ok(Channel::Guild(c)) => ChannelLocation { parent: channel_id, category: c.category_id },
+        _ => ChannelLocation { parent: channel_id, category: None },
+    }
+}
+
+// Whether r9k is enforced in a channel
+// In whitelist mode the listed channels are enforced, in blacklist mode every channel except the listed ones is
+// Listing a category covers every channel under it
+// Threads inherit their parent's status
+pub async fn is_enforced(database: &DatabaseTool, settings: &GuildSettings, location: &ChannelLocation) -> bool {
+    is_listed(database, settings.guild_id, location.parent, location.category).await != settings.blacklist
+}
+
+// Threads are treated as part of the channel they were created under, for enforcement as well as hashing
+// Returns the channel itself if it isn't a thread
+pub async fn parent_channel(ctx: &Context, channel_id: ChannelId) -> ChannelId {
+    match channel_id.to_channel(ctx).await {
+        // A thread's parent channel is deserialized into category_id
+        Ok(Channel::Guild(c)) if is_thread(&c) => c.category_id.unwrap_or(channel_id),
+        _ => channel_id,
+    }
+}
+
+pub fn is_thread(channel: &GuildChannel) -> bool {
+    matches!(channel.kind, ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread)
+}
+
+// Every text channel in a guild which r9k is enforced in, these are the channels mutes apply to
+pub async fn enforced_channels(ctx: &Context, database: &DatabaseTool, guild_id: u64) -> Vec<GuildChannel> {
+    let channels = match GuildId(guild_id).channels(&ctx.http).await {
+        Ok(c) => c,
+        Err(why) => {
+            println!("Unable to get guild channels [enforced_channels] Why: {:?}", why);
+            metrics::http_error("guild_channels");
+            return Vec::new();
+        }
+    };
+
+    let blacklist = is_blacklist(database, guild_id).await;
+    let listed = listed_channels(database, guild_id).await;
+
+    channels.into_values()
+        .filter(|c| matches!(c.kind, ChannelType::Text | ChannelType::News))
+        .filter(|c| {
+            let is_listed = listed.contains(&c.id.0) || c.category_id.is_some_and(|cat| listed.contains(&cat.0));
+            is_listed != blacklist
+        })
+        .collect()
+}
+
+async fn is_blacklist(database: &DatabaseTool, guild_id: u64) -> bool {
+    database.retrieve_bool("guild_settings", "blacklist", "id", &guild_id).await
+}
+
+async fn listed_channels(database: &DatabaseTool, guild_id: u64) -> Vec<u64> {
+    let channel_table = &format!("channels_{}", guild_id);
+    if database.table_exists(channel_table).await {
+        database.get_all_rows(channel_table, "id").await
+    } else {
+        Vec::new()
+    }
+}
+
+async fn is_listed(database: &DatabaseTool, guild_id: u64, channel_id: ChannelId, category_id: Option<ChannelId>) -> bool {
+    let listed = listed_channels(database, guild_id).await;
+    listed.contains(&channel_id.0) || category_id.is_some_and(|c| listed.contains(&c.0))
+}
+
+// How a mute came about
+// Automatic mutes follow the streak curve, manual mutes may carry their own duration which leaves the streak untouched
+// Reaction mutes follow their own, gentler curve and also leave the streak untouched
+pub enum Penalty {
+    Automatic {
+        hash: u128,
+        content: String,
+    },
+    Manual {
+        moderator: u64,
+        duration: Option<u64>,
+        reason: Option<String>,
+    },
+    Reaction {
+        emoji: String,
+    },
+}
+
+impl Penalty {
+    pub fn moderator(&self) -> u64 {
+        match self {
+            Penalty::Automatic { .. } | Penalty::Reaction { .. } => 0,
+            Penalty::Manual { moderator, .. } => *moderator,
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            Penalty::Manual { reason: Some(r), .. } => r,
+            _ => "",
+        }
+    }
+}
+
+// Longest a moderator can stunlock someone for, mute_until has to stay well below the i64::MAX that marks nobody as muted
+pub const MAX_MUTE_DURATION: u64 = 60 * 60 * 24 * 365;
+
+// Most warnings a guild can hand out before stunlocks begin
+pub const MAX_WARNINGS: u64 = 100;
+
+// Reaction stunlocks start at this many seconds and stop doubling after a few repeats
+const REACTION_BASE_DURATION: u64 = 30;
+const REACTION_MAX_DOUBLINGS: u64 = 7;
+
+// Column name, type, and default value for columns added after the stunlock tables were first introduced
+const STUNLOCK_COLUMNS: &[(&str, ColumnType, Option<&str>)] = &[
+    ("moderator", INTEGER, Some("0")),
+    ("reason", TEXT, None),
+    ("warnings", INTEGER, Some("0")),
+    ("warning_time", INTEGER, Some("0")),
+];
+
+// Creates the stunlock table for a guild if it doesn't exist yet, and adds any columns missing from older versions
+pub async fn stunlock_table(database: &DatabaseTool, guild_id: &u64) -> String {
+    let stunlock_table = format!("stunlocks_{}", guild_id);
+    if !database.table_exists(&stunlock_table).await {
+        database.create_table(&stunlock_table, &["id", "streak", "streak_time", "mute_until", "moderator", "reason", "warnings", "warning_time"], &[INTEGER, INTEGER, INTEGER, INTEGER, INTEGER, TEXT, INTEGER, INTEGER]).await;
+    } else {
+        for (column, column_type, default) in STUNLOCK_COLUMNS {
+            if !database.column_exists(&stunlock_table, column).await {
+                database.add_column(&stunlock_table, column, *column_type, *default).await;
+            }
+        }
     }
+
+    stunlock_table
 }
 
 #[derive(Clone, Copy)]
@@ -59,10 +224,13 @@ pub struct MuteInfo {
     pub streak: u64,
     pub streak_time: u64,
     pub mute_until: u64,
+    pub warnings: u64,
+    pub warning_time: u64,
 }
 
 impl MuteInfo {
-    async fn update(&mut self, ctx: &Context, guild_id: &u64, author_id: &u64) {
+    // Returns true once there is nothing left to track for the user, and their row has been dropped
+    async fn update(&mut self, ctx: &Context, guild_id: &u64, author_id: &u64) -> bool {
         let data = ctx.data.read().await;
         let database = data.get::<Database>().expect("Expected Database in TypeMap");
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
@@ -70,24 +238,20 @@ impl MuteInfo {
 
         // Unmute User
         if now >= self.mute_until {
-            for cid in database.get_all_rows(&format!("channels_{}", guild_id), "id").await {
-                if let Ok(channel) = ChannelId(cid).to_channel(&ctx.http).await {
-                    if let Some(mut guild_channel) = channel.guild() {
-                        // Update values
-                        database.update_int(stunlock_table, "mute_until", &(i64::MAX as u64), author_id).await;
-                        self.mute_until = i64::MAX as u64;
-
-                        // Update perms
-                        let _ = guild_channel.edit(&ctx.http, |c| {
-                            c.permissions(MutePermissions::unmute(*author_id))
-                        }).await;
-                        
-                        // Notify user
-                        if let Ok(user) = UserId(*author_id).to_user(&ctx.http).await {
-                            embeds::unmute(ctx, &user, guild_id).await;
-                        }
-                    }
-                }
+            // Update values
+            database.update_int(stunlock_table, "mute_until", &(i64::MAX as u64), author_id).await;
+            self.mute_until = i64::MAX as u64;
+
+            // Update perms
+            for guild_channel in enforced_channels(ctx, database, *guild_id).await {
+                let _ = guild_channel.delete_permission(&ctx.http, PermissionOverwriteType::Member(UserId(*author_id))).await;
+            }
+
+            metrics::unmute();
+
+            // Notify user
+            if let Ok(user) = UserId(*author_id).to_user(&ctx.http).await {
+                embeds::unmute(ctx, &user, guild_id).await;
             }
         }
 
@@ -98,26 +262,22 @@ impl MuteInfo {
             let new_streak = self.streak.saturating_sub(decrease_by);
             let new_streak_time = self.streak_time + (decrease_by * 21600);
 
-            if new_streak != self.streak || self.streak == 0 {
-                if self.streak == 0 {
-                    // Drop info on a user if they no longer have a streak
-                    database.delete_row(stunlock_table, "id", author_id).await;
-                    
-                    let data = ctx.data.read().await;
-                    let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
-                    let mut mute_cache = mute_arc.write().await;
-                    mute_cache.entry(*guild_id).and_modify(|guild_data| {
-                        guild_data.remove(author_id);
-                    });
-                } else {
-                    database.update_int(stunlock_table, "streak", &new_streak, author_id).await;
-                    database.update_int(stunlock_table, "streak_time", &new_streak_time, author_id).await;
-
-                    self.streak_time = new_streak_time;
-                    self.streak = new_streak;
-                }
+            if new_streak != self.streak {
+                database.update_int(stunlock_table, "streak", &new_streak, author_id).await;
+                database.update_int(stunlock_table, "streak_time", &new_streak_time, author_id).await;
+
+                self.streak_time = new_streak_time;
+                self.streak = new_streak;
             }
         }
+
+        // Drop info on a user once they have no streak, no mute, and no warnings left in the decay window
+        if self.streak == 0 && self.mute_until == i64::MAX as u64 && now.saturating_sub(self.warning_time) >= 21600 {
+            database.delete_row(stunlock_table, "id", author_id).await;
+            return true;
+        }
+
+        false
     }
     
     // When the mutee is not in the database
@@ -125,84 +285,184 @@ impl MuteInfo {
         let a = Self {
             streak: 0,
             streak_time: 0,
-            mute_until: 0,
+            mute_until: i64::MAX as u64,
+            warnings: 0,
+            warning_time: 0,
         };
         a
     } 
 
-    async fn mute(&mut self, ctx: &Context, msg: &FauxMessage) {
+    // Uses up one of the guild's free violations instead of muting
+    // Returns false if the guild doesn't allow any or the user has none left in the current decay window
+    async fn warn(&mut self, ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, hash: u128) -> bool {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+        let allowed = database.retrieve_int("guild_settings", "warnings", "id", &guild_id).await as u64;
+        if allowed == 0 {
+            return false;
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        if now.saturating_sub(self.warning_time) >= 21600 {
+            self.warnings = 0;
+            self.warning_time = now;
+        }
+
+        if self.warnings >= allowed {
+            return false;
+        }
+        self.warnings += 1;
+
+        // Record
+        let author_id = &offender.id.0;
+        let stunlock_table = &stunlock_table(database, &guild_id).await;
+        if database.row_exists(stunlock_table, "id", author_id).await {
+            database.update_int(stunlock_table, "warnings", &self.warnings, author_id).await;
+            database.update_int(stunlock_table, "warning_time", &self.warning_time, author_id).await;
+        } else {
+            database.insert_row(stunlock_table, &[&to_string(author_id), &to_string(self.streak), &to_string(self.streak_time), &to_string(self.mute_until), &to_string(0), "", &to_string(self.warnings), &to_string(self.warning_time)]).await;
+        }
+
+        history::record(database, HistoryRecord {
+            guild: guild_id,
+            user: *author_id,
+            channel: channel_id.0,
+            kind: HistoryKind::Warning,
+            duration: 0,
+            streak: self.streak,
+            hash: Some(hash),
+            moderator: 0,
+            reason: "",
+        }).await;
+
+        // Send a message
+        embeds::warning(ctx, guild_id, channel_id, offender, allowed - self.warnings).await;
+        embeds::log(ctx, &guild_id, LogEntry::Warning {
+            user: *author_id,
+            channel: channel_id.0,
+            remaining: allowed - self.warnings,
+        }).await;
+
+        true
+    }
+
+    async fn mute(&mut self, ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, penalty: &Penalty) {
+        if let Penalty::Automatic { hash, .. } = penalty {
+            if self.warn(ctx, guild_id, channel_id, offender, *hash).await {
+                return;
+            }
+        }
+
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        
-        // Increase the user's streak
-        let new_streak = self.streak + 1;
-        let duration = 2u64.pow(2 * new_streak as u32 - 1);
-        let new_mute_until = now + duration;
 
         let data = ctx.data.read().await;
         let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        
+        // Increase the user's streak, unless a moderator specified how long the mute should last
+        let (new_streak, duration) = match penalty {
+            Penalty::Manual { duration: Some(d), .. } => (self.streak, (*d).min(MAX_MUTE_DURATION)),
+            Penalty::Reaction { .. } => {
+                // Doubles with every reaction stunlock in the streak decay window, starting at half a minute
+                let recent = history::count_since(database, guild_id, offender.id.0, HistoryKind::Reaction, now.saturating_sub(21600)).await;
+                (self.streak, REACTION_BASE_DURATION * 2u64.pow(recent.min(REACTION_MAX_DOUBLINGS) as u32))
+            }
+            _ => {
+                let new_streak = self.streak + 1;
+                (new_streak, 2u64.pow(2 * new_streak as u32 - 1))
+            }
+        };
+        let new_mute_until = now + duration;
+        // Only a growing streak restarts its decay, a moderator picking the duration or a reaction leaves it alone
+        let streak_time = match penalty {
+            Penalty::Manual { duration: Some(_), .. } | Penalty::Reaction { .. } => self.streak_time,
+            _ => now,
+        };
 
         // Record
-        let author_id = &msg.author.id.0;
-        let stunlock_table = &format!("stunlocks_{}", &msg.guild_id);
-        if !database.table_exists(stunlock_table).await {
-            database.create_table(stunlock_table, &vec!["id", "streak", "streak_time", "mute_until"], &vec![INTEGER, INTEGER, INTEGER, INTEGER]).await;
-        }
+        let author_id = &offender.id.0;
+        let stunlock_table = &stunlock_table(database, &guild_id).await;
 
         if database.row_exists(stunlock_table, "id", author_id).await {
             database.update_int(stunlock_table, "streak", &new_streak, author_id).await;
-            database.update_int(stunlock_table, "streak_time", &now, author_id).await;
+            database.update_int(stunlock_table, "streak_time", &streak_time, author_id).await;
             database.update_int(stunlock_table, "mute_until", &new_mute_until, author_id).await;
+            database.update_int(stunlock_table, "moderator", &penalty.moderator(), author_id).await;
+            database.update_str(stunlock_table, "reason", penalty.reason(), author_id).await;
         } else {
-            database.insert_row(stunlock_table, &[&to_string(author_id), &to_string(new_streak), &to_string(now), &to_string(new_mute_until)]).await;
+            database.insert_row(stunlock_table, &[&to_string(author_id), &to_string(new_streak), &to_string(streak_time), &to_string(new_mute_until), &to_string(penalty.moderator()), penalty.reason(), &to_string(self.warnings), &to_string(self.warning_time)]).await;
         }
         
         self.streak = new_streak;
         self.mute_until = new_mute_until;
-        self.streak_time = now;
+        self.streak_time = streak_time;
+
+        // Statistics for threads are counted towards their parent channel
+        stats::increment(database, guild_id, parent_channel(ctx, channel_id).await.0, Counter::Mutes).await;
+        metrics::mute(match penalty {
+            Penalty::Automatic { .. } => "automatic",
+            Penalty::Manual { .. } => "manual",
+            Penalty::Reaction { .. } => "reaction",
+        });
+
+        history::record(database, HistoryRecord {
+            guild: guild_id,
+            user: *author_id,
+            channel: channel_id.0,
+            kind: match penalty {
+                Penalty::Automatic { .. } => HistoryKind::Automatic,
+                Penalty::Manual { .. } => HistoryKind::Manual,
+                Penalty::Reaction { .. } => HistoryKind::Reaction,
+            },
+            duration,
+            streak: new_streak,
+            hash: match penalty {
+                Penalty::Automatic { hash, .. } => Some(*hash),
+                Penalty::Manual { .. } | Penalty::Reaction { .. } => None,
+            },
+            moderator: penalty.moderator(),
+            reason: penalty.reason(),
+        }).await;
 
         // Update perms
-        for cid in database.get_all_rows(&format!("channels_{}", msg.guild_id), "id").await {
-            if let Ok(channel) = ChannelId(cid).to_channel(&ctx.http).await {
-                if let Some(mut guild_channel) = channel.guild() {
-                    match guild_channel.edit(&ctx.http, |c| {
-                        c.permissions(MutePermissions::mute(*author_id))
-                    }).await {
-                        Err(why) => println!("{:?}", why),
-                        _ => {},
-                    }
+        for guild_channel in enforced_channels(ctx, database, guild_id).await {
+            for overwrite in PermissionOverwrite::mute(*author_id) {
+                if let Err(why) = guild_channel.create_permission(&ctx.http, &overwrite).await {
+                    println!("{:?}", why);
+                    metrics::http_error("create_permission");
                 }
             }
         }
 
         // Send a message
-        embeds::stunlock(&ctx, msg, duration, new_streak).await;
+        embeds::stunlock(ctx, guild_id, channel_id, offender, duration, new_streak, penalty).await;
+        embeds::log(ctx, &guild_id, LogEntry::Mute {
+            user: *author_id,
+            channel: channel_id.0,
+            duration,
+            streak: new_streak,
+            penalty,
+        }).await;
+        if let Penalty::Manual { .. } = penalty {
+            embeds::stunlock_dm(ctx, offender, &guild_id, channel_id, duration, penalty).await;
+        }
     } 
 }
 
-// Only the author, channel_id, and guild_id fields from FauxMessage are needed to run this function
-// I'm not happy with the way this looks now, but I feel like passing 2 extra fields would just make it look worse
-pub async fn mute(ctx: &Context, msg: &FauxMessage) {
+pub async fn mute(ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, penalty: &Penalty) {
     let data = ctx.data.read().await;
     let mute_arc = data.get::<MuteCache>().expect("Expected MuteCache in TypeMap");
     let mut mute_cache = mute_arc.write().await;
-    let author_id = msg.author.id.0;
+    let author_id = offender.id.0;
 
-    if let Some(guild_data) = mute_cache.get_mut(&msg.guild_id) {
-        // Data present for the guild
-        if let Some(author_data) = guild_data.get_mut(&author_id) {
-            // Data present for the user
-            author_data.mute(ctx, msg).await;
-        } else {
-            // No data present for the user
-            let mut author_data = MuteInfo::new_mute().await;
-            author_data.mute(ctx, msg).await;
-            guild_data.insert(author_id, author_data);
-        }
+    let guild_data = mute_cache.entry(guild_id).or_insert_with(HashMap::new);
+    if let Some(author_data) = guild_data.get_mut(&author_id) {
+        // Data present for the user
+        author_data.mute(ctx, guild_id, channel_id, offender, penalty).await;
     } else {
-        // No data present for the guild or user
-        let mut guild_data = HashMap::new();
-        let author_data = MuteInfo::new_mute().await;
+        // No data present for the user
+        let mut author_data = MuteInfo::new_mute().await;
+        author_data.mute(ctx, guild_id, channel_id, offender, penalty).await;
         guild_data.insert(author_id, author_data);
-        mute_cache.insert(msg.guild_id, guild_data);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,238 @@
+use std::{env, fs, net::SocketAddr, str::FromStr};
+
+use serde::Deserialize;
+
+// Read when CONFIG_FILE isn't set, the file is optional as long as the environment covers everything
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub discord: DiscordConfig,
+    pub database: DatabaseConfig,
+    pub bot: BotConfig,
+    pub metrics: MetricsConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub token: String,
+    // How many messages serenity keeps cached per channel
+    pub message_cache: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub username: String,
+    pub password: String,
+    pub name: String,
+    pub max_connections: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+    pub salt: String,
+    pub default_prefix: String,
+    // Seconds between passes of the loop that lifts stunlocks and decays streaks
+    pub loop_interval: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    // Where `/metrics` is served, left empty to not serve metrics at all
+    pub address: String,
+}
+
+impl MetricsConfig {
+    pub fn address(&self) -> Option<SocketAddr> {
+        self.address.parse().ok()
+    }
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        DiscordConfig {
+            token: String::new(),
+            message_cache: 10000,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            host: String::new(),
+            username: String::new(),
+            password: String::new(),
+            name: String::new(),
+            max_connections: 5,
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            address: "127.0.0.1:9184".to_string(),
+        }
+    }
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        BotConfig {
+            salt: String::new(),
+            default_prefix: "9!".to_string(),
+            loop_interval: 2,
+        }
+    }
+}
+
+// Environment variables win over the file, so secrets can stay out of it
+fn override_str(value: &mut String, var: &str) {
+    if let Ok(v) = env::var(var) {
+        *value = v;
+    }
+}
+
+fn override_num<T: FromStr>(problems: &mut Vec<String>, value: &mut T, var: &str) {
+    if let Ok(v) = env::var(var) {
+        match v.trim().parse::<T>() {
+            Ok(n) => *value = n,
+            Err(_) => problems.push(format!("{} must be a whole number, got `{}`", var, v)),
+        }
+    }
+}
+
+impl Config {
+    // Reads the config file, applies the environment on top and checks the result
+    // Returns a description of every problem found, so they can all be fixed at once
+    pub fn load() -> Result<Config, Vec<String>> {
+        let mut problems = Vec::new();
+
+        let path = env::var("CONFIG_FILE").ok();
+        let mut config = match fs::read_to_string(path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE)) {
+            Ok(source) => match toml::from_str::<Config>(&source) {
+                Ok(c) => c,
+                Err(why) => return Err(vec![format!("Could not parse {}: {}", path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE), why)]),
+            },
+            // Only a missing default file is fine, one that was asked for has to exist
+            Err(why) => match path {
+                Some(p) => return Err(vec![format!("Could not read {}: {}", p, why)]),
+                None => Config::default(),
+            },
+        };
+
+        override_str(&mut config.discord.token, "DISCORD_TOKEN");
+        override_num(&mut problems, &mut config.discord.message_cache, "MESSAGE_CACHE");
+        override_str(&mut config.database.host, "MYSQL_HOST");
+        override_str(&mut config.database.username, "MYSQL_USERNAME");
+        override_str(&mut config.database.password, "MYSQL_PASSWORD");
+        override_str(&mut config.database.name, "MYSQL_DB");
+        override_num(&mut problems, &mut config.database.max_connections, "MYSQL_MAX_CONNECTIONS");
+        override_str(&mut config.bot.salt, "SALT");
+        override_str(&mut config.bot.default_prefix, "DEFAULT_PREFIX");
+        override_num(&mut problems, &mut config.bot.loop_interval, "LOOP_INTERVAL");
+        override_str(&mut config.metrics.address, "METRICS_ADDRESS");
+
+        problems.extend(config.validate());
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(problems)
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let required = [
+            (&self.discord.token, "discord.token", "DISCORD_TOKEN"),
+            (&self.database.host, "database.host", "MYSQL_HOST"),
+            (&self.database.username, "database.username", "MYSQL_USERNAME"),
+            (&self.database.name, "database.name", "MYSQL_DB"),
+            (&self.bot.salt, "bot.salt", "SALT"),
+        ];
+        for (value, key, var) in required {
+            if value.trim().is_empty() {
+                problems.push(format!("Missing `{}`, set it in the config file or with {}", key, var));
+            }
+        }
+
+        if self.database.max_connections == 0 {
+            problems.push("`database.max_connections` must be at least 1".to_string());
+        }
+        if self.bot.loop_interval == 0 {
+            problems.push("`bot.loop_interval` must be at least 1 second".to_string());
+        }
+        // Prefixes are parsed out of messages, so they can't contain whitespace
+        if self.bot.default_prefix.is_empty() || self.bot.default_prefix.contains(char::is_whitespace) {
+            problems.push("`bot.default_prefix` must be non-empty and can't contain whitespace".to_string());
+        }
+        if !self.metrics.address.is_empty() && self.metrics.address().is_none() {
+            problems.push(format!("`metrics.address` must be an address like `127.0.0.1:9184`, got `{}`", self.metrics.address));
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete() -> Config {
+        toml::from_str(r#"
+            [discord]
+            token = "token"
+            [database]
+            host = "localhost"
+            username = "r9k"
+            name = "r9k"
+            [bot]
+            salt = "salt"
+        "#).unwrap()
+    }
+
+    #[test]
+    fn complete_config_is_valid() {
+        assert!(complete().validate().is_empty());
+        // Only the connection details are left to fill in
+        let example: Config = toml::from_str(include_str!("../../config.example.toml")).unwrap();
+        assert_eq!(example.validate().len(), 4);
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut config = complete();
+        config.discord.token = " ".to_string();
+        config.bot.salt = String::new();
+        config.database.max_connections = 0;
+        config.bot.loop_interval = 0;
+        config.bot.default_prefix = "9 !".to_string();
+        config.metrics.address = "localhost".to_string();
+
+        let problems = config.validate();
+        assert_eq!(problems.len(), 6);
+        assert!(problems[0].contains("discord.token") && problems[0].contains("DISCORD_TOKEN"));
+        assert!(problems[1].contains("bot.salt"));
+    }
+
+    #[test]
+    fn metrics_can_be_disabled() {
+        let mut config = complete();
+        config.metrics.address = String::new();
+        assert!(config.validate().is_empty());
+        assert!(config.metrics.address().is_none());
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<Config>("[bot]\nprefix = \"!\"").is_err());
+    }
+}
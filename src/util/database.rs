@@ -1,9 +1,15 @@
-use std::{fmt::{Display, Debug}, env};
+use std::fmt::{Display, Debug};
 use sqlx::{Row, MySqlPool};
 
+use super::metrics;
+
 #[derive(Clone)]
 pub struct DatabaseTool {
     pub pool: MySqlPool,
+    // The database the pool connects to, needed to look tables up in information_schema
+    pub schema: String,
+    // Given to guilds until they pick their own
+    pub default_prefix: String,
 }
 
 pub trait ValidValue: 'static + Display + Debug {}
@@ -27,7 +33,7 @@ impl ValidInt for i64 {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ColumnType {
     which: &'static str,
 }
@@ -35,9 +41,17 @@ pub struct ColumnType {
 pub const TEXT: ColumnType = ColumnType {which: "TEXT"};
 pub const INTEGER: ColumnType = ColumnType {which: "BIGINT"};
 pub const BOOL: ColumnType = ColumnType {which: "BOOLEAN"};
+// Unlike TEXT, MySQL allows short text columns to have a default
+pub const SHORT_TEXT: ColumnType = ColumnType {which: "VARCHAR(32)"};
+
+// Escapes a string so that it can be placed between single quotes in a query
+pub fn scrub(value: &str) -> String {
+    value.replace("\\", "\\\\").replace("'", "''")
+}
 
 impl DatabaseTool {
     pub async fn retrieve_str<T: ValidInt>(&self, table: &str, wanted_column: &str, seeking_column: &str, id: &T) -> String {
+        let _timer = metrics::query_timer("retrieve_str");
         let row: (String,) = sqlx::query_as(format!("SELECT {} FROM {} WHERE {} = {}", wanted_column, table, seeking_column, id.as_i64()).as_str())
         .fetch_one(&self.pool)
         .await
@@ -47,6 +61,7 @@ impl DatabaseTool {
     }
 
     pub async fn retrieve_int<T: ValidInt>(&self, table: &str, wanted_column: &str, seeking_column: &str, seeking_id: &T) -> i64 {
+        let _timer = metrics::query_timer("retrieve_int");
         let row: (i64,) = sqlx::query_as(format!("SELECT {} FROM {} WHERE {} = {}", wanted_column, table, seeking_column, seeking_id.as_i64()).as_str())
         .fetch_one(&self.pool)
         .await
@@ -56,6 +71,7 @@ impl DatabaseTool {
     }
 
     pub async fn retrieve_bool<T: ValidInt>(&self, table: &str, wanted_column: &str, seeking_column: &str, seeking_id: &T) -> bool {
+        let _timer = metrics::query_timer("retrieve_bool");
         let row: (bool,) = sqlx::query_as(format!("SELECT {} FROM {} WHERE {} = {}", wanted_column, table, seeking_column, seeking_id.as_i64()).as_str())
         .fetch_one(&self.pool)
         .await
@@ -65,12 +81,8 @@ impl DatabaseTool {
     }
 
     pub async fn update_str<T: ValidInt>(&self, table: &str, column: &str, value: &str, id: &T) {
-        let value_scrubbed: String;
-        if value.contains("'") {
-            value_scrubbed = value.replace("'", "''");
-        } else {
-            value_scrubbed = String::from(value);
-        }
+        let _timer = metrics::query_timer("update_str");
+        let value_scrubbed = scrub(value);
         sqlx::query(format!("UPDATE {} SET {} = '{}' WHERE id = {}", table, column, value_scrubbed, id.as_i64()).as_str())
         .execute(&self.pool)
         .await
@@ -78,6 +90,7 @@ impl DatabaseTool {
     }
 
     pub async fn update_int<T: ValidInt, U: ValidInt>(&self, table: &str, column: &str, value: &T, id: &U) {
+        let _timer = metrics::query_timer("update_int");
         sqlx::query(format!("UPDATE {} SET {} = {} WHERE id = {}", table, column, value.as_i64(), id.as_i64()).as_str())
         .execute(&self.pool)
         .await
@@ -85,6 +98,7 @@ impl DatabaseTool {
     }
 
     pub async fn update_bool<U: ValidInt>(&self, table: &str, column: &str, value: bool, id: &U) {
+        let _timer = metrics::query_timer("update_bool");
         let value_str: &str;
         if value {
             value_str = "1";
@@ -100,6 +114,7 @@ impl DatabaseTool {
 
     /// Returns true if a row exists in a given table, with a given value, at a given column
     pub async fn row_exists<T: ValidValue>(&self, table: &str, column_name: &str, value: &T) -> bool{
+        let _timer = metrics::query_timer("row_exists");
         // let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT 1 FROM {} WHERE {} = '{}')", table, column_name, value).as_str())
         let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT {} FROM {} WHERE {} = '{}')", column_name, table, column_name, value).as_str())
         .fetch_one(&self.pool)
@@ -111,6 +126,7 @@ impl DatabaseTool {
 
     // Should probably increase safety on this at some point
     pub async fn insert_row(&self, table: &str, values: &[&str]) {
+        let _timer = metrics::query_timer("insert_row");
         let mut qry = format!("INSERT INTO {} VALUES (", table);
         
         for s in values {
@@ -134,7 +150,7 @@ impl DatabaseTool {
                 _ => (),
             }
 
-            qry.push_str(&format!("'{}', ", s));
+            qry.push_str(&format!("'{}', ", scrub(s)));
         }
 
         qry.pop();
@@ -148,6 +164,7 @@ impl DatabaseTool {
     }
 
     pub async fn delete_row<T: ValidValue>(&self, table: &str, column_name: &str, value: &T) {
+        let _timer = metrics::query_timer("delete_row");
         sqlx::query(format!("DELETE FROM {} WHERE {} = {}", table, column_name, value).as_str())
         .execute(&self.pool)
         .await
@@ -155,7 +172,8 @@ impl DatabaseTool {
     }
 
     pub async fn table_exists(&self, table: &str) -> bool {
-        let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT table_name FROM information_schema.tables WHERE table_schema = '{}' AND table_name = '{}')", env::var("MYSQL_DB").unwrap(), table).as_str())
+        let _timer = metrics::query_timer("table_exists");
+        let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT table_name FROM information_schema.tables WHERE table_schema = '{}' AND table_name = '{}')", scrub(&self.schema), table).as_str())
         .fetch_one(&self.pool)
         .await
         .expect("Could not query database [table_exists]");
@@ -165,6 +183,7 @@ impl DatabaseTool {
 
     // Should probably increase safety on this at some point
     pub async fn create_table(&self, table: &str, keys: &[&str], types: &[ColumnType]) {
+        let _timer = metrics::query_timer("create_table");
         // Make sure there's a key for every declared type
         let keys_len = keys.len();
         if keys_len != types.len() {
@@ -188,6 +207,7 @@ impl DatabaseTool {
     }
 
     pub async fn get_all_rows(&self, table: &str, key: &str) -> Vec<u64> {
+        let _timer = metrics::query_timer("get_all_rows");
         let result = sqlx::query(format!("SELECT {} FROM {}", key, table).as_str())
         .fetch_all(&self.pool)
         .await
@@ -197,4 +217,82 @@ impl DatabaseTool {
             .map(|i| i.get::<i64, usize>(0) as u64)
             .collect()
     }
-}
\ No newline at end of file
+
+    /// Inserts the default settings for a guild if it doesn't have any yet
+    pub async fn ensure_guild(&self, guild_id: &u64) {
+        let _timer = metrics::query_timer("ensure_guild");
+        if !self.row_exists("guild_settings", "id", guild_id).await {
+            sqlx::query(format!("INSERT INTO guild_settings (id, prefix, global) VALUES ({}, '{}', 1)", guild_id, scrub(&self.default_prefix)).as_str())
+            .execute(&self.pool)
+            .await
+            .expect("Could not insert into database [ensure_guild]");
+        }
+    }
+
+    /// The guild's command prefix, or the default one outside of guilds and for guilds without settings yet
+    pub async fn prefix(&self, guild_id: u64) -> String {
+        let _timer = metrics::query_timer("prefix");
+        if guild_id == 0 {
+            return self.default_prefix.clone();
+        }
+
+        let row: Option<(String,)> = sqlx::query_as(format!("SELECT prefix FROM guild_settings WHERE id = {}", guild_id).as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .expect("Could not query database [prefix]");
+
+        row.map_or_else(|| self.default_prefix.clone(), |r| r.0)
+    }
+
+    pub async fn column_exists(&self, table: &str, column: &str) -> bool {
+        let _timer = metrics::query_timer("column_exists");
+        let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT column_name FROM information_schema.columns WHERE table_schema = '{}' AND table_name = '{}' AND column_name = '{}')", scrub(&self.schema), table, column).as_str())
+        .fetch_one(&self.pool)
+        .await
+        .expect("Could not query database [column_exists]");
+
+        row.0
+    }
+
+    pub async fn index_exists(&self, table: &str, index: &str) -> bool {
+        let _timer = metrics::query_timer("index_exists");
+        let row: (bool,) = sqlx::query_as(format!("SELECT EXISTS(SELECT index_name FROM information_schema.statistics WHERE table_schema = '{}' AND table_name = '{}' AND index_name = '{}')", scrub(&self.schema), table, index).as_str())
+        .fetch_one(&self.pool)
+        .await
+        .expect("Could not query database [index_exists]");
+
+        row.0
+    }
+
+    /// Makes a combination of columns unique, rows that already repeat it have to be merged beforehand
+    pub async fn add_unique_key(&self, table: &str, index: &str, columns: &[&str]) {
+        let _timer = metrics::query_timer("add_unique_key");
+        sqlx::query(format!("ALTER TABLE {} ADD UNIQUE KEY {} ({})", table, index, columns.join(", ")).as_str())
+        .execute(&self.pool)
+        .await
+        .expect("Could not alter table [add_unique_key]");
+    }
+
+    /// Indexes a combination of columns, for lookups and deletions which would otherwise scan the whole table
+    pub async fn add_index(&self, table: &str, index: &str, columns: &[&str]) {
+        let _timer = metrics::query_timer("add_index");
+        sqlx::query(format!("ALTER TABLE {} ADD INDEX {} ({})", table, index, columns.join(", ")).as_str())
+        .execute(&self.pool)
+        .await
+        .expect("Could not alter table [add_index]");
+    }
+
+    /// Adds a column to an already existing table, used to bring tables created by older versions up to date
+    pub async fn add_column(&self, table: &str, column: &str, column_type: ColumnType, default: Option<&str>) {
+        let _timer = metrics::query_timer("add_column");
+        let qry = match default {
+            Some(d) => format!("ALTER TABLE {} ADD COLUMN {} {} NOT NULL DEFAULT {}", table, column, column_type.which, d),
+            None => format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, column_type.which),
+        };
+
+        sqlx::query(&qry)
+        .execute(&self.pool)
+        .await
+        .expect("Could not alter table [add_column]");
+    }
+}
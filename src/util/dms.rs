@@ -0,0 +1,21 @@
+use super::database::{DatabaseTool, INTEGER};
+
+// Users who don't want the bot to DM them, shared across every guild
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("dm_opt_out").await {
+        database.create_table("dm_opt_out", &["id"], &[INTEGER]).await;
+    }
+}
+
+pub async fn is_opted_out(database: &DatabaseTool, user_id: u64) -> bool {
+    database.row_exists("dm_opt_out", "id", &user_id).await
+}
+
+pub async fn set_opted_out(database: &DatabaseTool, user_id: u64, opted_out: bool) {
+    let present = is_opted_out(database, user_id).await;
+    if opted_out && !present {
+        database.insert_row("dm_opt_out", &[&user_id.to_string()]).await;
+    } else if !opted_out && present {
+        database.delete_row("dm_opt_out", "id", &user_id).await;
+    }
+}
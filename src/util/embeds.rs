@@ -4,23 +4,44 @@ use std::{
 };
 
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
     utils::Color,
     model::{
         channel::Message,
         prelude::User,
-        id::GuildId,
+        id::{GuildId, ChannelId},
+        interactions::{
+            InteractionResponseType,
+            InteractionApplicationCommandCallbackDataFlags,
+            application_command::ApplicationCommandInteraction,
+        },
     },
     Result as SerenityResult,
 };
 
-use crate::{events::on_message::FauxMessage, Database};
+use crate::Database;
 
-use super::misc::{seconds_to_string, check_msg, to_string};
+use super::{
+    misc::{seconds_to_string, check_msg, to_string, truncate},
+    locale::{tr, guild_locale},
+    check::Penalty,
+    history::HistoryEntry,
+    stats::{self, ChannelStats, UserStats, Counter},
+    template::{self, Template, TemplateKind, TITLE_LIMIT, DESCRIPTION_LIMIT},
+    notices::{self, NoticeMode},
+    history,
+    dms,
+    metrics,
+};
 
 const DEFAULT_COLOR: Color = Color::from_rgb(149, 165, 166);
 const ERROR_COLOR: Color = Color::from_rgb(231, 76, 60);
 const SETTINGS_COLOR: Color = Color::from_rgb(13, 71, 161);
+const WARNING_COLOR: Color = Color::from_rgb(241, 196, 15);
+
+// How much of each reason is shown in a history page
+const HISTORY_REASON_LIMIT: usize = 200;
 
 
 // Sends a message and deletes it after a certian amount of time
@@ -34,7 +55,28 @@ async fn temp_msg(ctx: &Context, duration: u64, result: SerenityResult<Message>)
                 let _ = msg.delete(http).await; 
             });
         }
-        Err(why) => println!("Error sending message: {:?}", why),
+        Err(why) => {
+            println!("Error sending message: {:?}", why);
+            metrics::http_error("send_message");
+        }
+    }
+}
+
+// Responds to a slash command with an embed
+// Ephemeral responses are only shown to the user who ran the command
+pub async fn respond(ctx: &Context, command: &ApplicationCommandInteraction, embed: CreateEmbed, ephemeral: bool) {
+    if let Err(why) = command.create_interaction_response(&ctx.http, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.add_embed(embed);
+            if ephemeral {
+                d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            }
+            d
+        })
+    }).await {
+        println!("Error responding to interaction: {:?}", why);
+        metrics::http_error("interaction_response");
     }
 }
 
@@ -42,11 +84,7 @@ async fn temp_msg(ctx: &Context, duration: u64, result: SerenityResult<Message>)
 async fn get_prefix(ctx: &Context, msg: &Message) -> String {
     let data = ctx.data.read().await;
     let database = data.get::<Database>().expect("Expected Database in TypeMap");
-    
-    match msg.guild_id {
-        Some(gid) => return database.retrieve_str("guild_settings", "prefix", "id", &gid.0).await,
-        None => return to_string("9!"),
-    }
+    database.prefix(msg.guild_id.map_or(0, |g| g.0)).await
 }
 
 
@@ -63,43 +101,41 @@ pub enum Meta {
 }
 
 #[allow(unreachable_patterns)]
+pub fn meta_embed(meta: Meta, args: &[&str]) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.color(DEFAULT_COLOR);
+    e.description(
+        if !args.is_empty() {
+            panic!("Specified embed with argurments when it shouldn't have been [meta]")
+        } else {
+            match meta {
+                Meta::Ping => "Pong!",
+                _ => panic!("Specified embed was not provided with arguments [meta]"),
+            }.to_string()
+        }
+    );
+    e
+}
+
 pub async fn meta(ctx: &Context, msg: &Message, meta: Meta, args: &[&str]) {
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
-        m.embed(|e| {
-            e.color(DEFAULT_COLOR);
-            e.description(
-                if !args.is_empty() {              
-                    match meta {
-                        _ => panic!("Specified embed with argurments when it shouldn't have been [meta]"),
-                    }   
-                } else {
-                    match meta {
-                        Meta::Ping => "Pong!",
-                        _ => panic!("Specified embed was not provided with arguments [meta]"),
-                    }.to_string()
-                }
-            );
-            e
-        });
+        m.set_embed(meta_embed(meta, args));
         m
     }).await);
 }
 
+pub fn help_embed(locale: &str) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.title(tr(locale, "help-title", &[]));
+    e.color(DEFAULT_COLOR);
+    e.description(tr(locale, "help-description", &[]));
+    e
+}
+
 pub async fn help(ctx: &Context, msg: &Message) {
-    check_msg(msg.channel_id.send_message(ctx, |m| m.embed(|e| {
-        e.title("Commands");
-        e.color(DEFAULT_COLOR);
-        e.description("System
-        - **Help** Show this message.
-        - **Ping** Pong!
-        - **Settings** Change how the bot behaves in this server.
-        
-        Moderation
-        - **Stunlock** Manually stunlock a user.
-        - **Streak** Modify a user's streak.");
-        e
-    })).await);
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    check_msg(msg.channel_id.send_message(ctx, |m| m.set_embed(help_embed(&locale))).await);
 }
 
 
@@ -121,66 +157,241 @@ pub enum Setting {
     Malformed,
     EnabledGlobal,
     DisabledGlobal,
+    SetLogChannel,
+    DisabledLogChannel,
+    EnabledLogContent,
+    DisabledLogContent,
+    AddedExemption,
+    RemovedExemption,
+    NoUser,
+    NoRole,
+    EnabledRecordExempt,
+    DisabledRecordExempt,
+    CurrentWarnings,
+    ChangedWarnings,
+    WhitelistMode,
+    BlacklistMode,
+    ChangedRetract,
+    DisabledRetract,
+    CurrentEditPolicy,
+    ChangedEditPolicy,
+    CurrentReactionMode,
+    ChangedReactionMode,
+    EnabledReplyContext,
+    DisabledReplyContext,
+    CurrentMarkdown,
+    ChangedMarkdown,
+    EnabledLinks,
+    DisabledLinks,
+    EnabledEmoji,
+    DisabledEmoji,
+    CurrentLanguage,
+    ChangedLanguage,
+    UnknownLanguage,
+    CurrentTemplate,
+    ChangedTemplate,
+    ResetTemplate,
+    UnknownTemplate,
+    InvalidTemplate,
+    CurrentNotices,
+    ChangedNotices,
+    NoLogChannel,
+    EnabledDms,
+    DisabledDms,
+}
+
+impl Setting {
+    pub fn is_error(&self) -> bool {
+        matches!(self, Setting::NoChannel | Setting::Malformed | Setting::NoUser | Setting::NoRole | Setting::UnknownLanguage
+            | Setting::UnknownTemplate | Setting::InvalidTemplate | Setting::NoLogChannel)
+    }
+}
+
+pub fn setting_embed(locale: &str, setting: &Setting, args: &[&str]) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.color(SETTINGS_COLOR);
+    e.description(
+        if !args.is_empty() {              
+            match setting {
+                Setting::ChangedPrefix => tr(locale, "setting-changed-prefix", &[("prefix", args[0])]),
+                Setting::CurrentPrefix => tr(locale, "setting-current-prefix", &[("prefix", args[0])]),
+                Setting::AddedChannel => tr(locale, "setting-added-channel", &[("channel", args[0])]),
+                Setting::RemovedChannel => tr(locale, "setting-removed-channel", &[("channel", args[0])]),
+                Setting::SetLogChannel => tr(locale, "setting-set-log-channel", &[("channel", args[0])]),
+                Setting::AddedExemption => tr(locale, "setting-added-exemption", &[("target", args[0])]),
+                Setting::RemovedExemption => tr(locale, "setting-removed-exemption", &[("target", args[0])]),
+                Setting::CurrentWarnings => tr(locale, "setting-current-warnings", &[("warnings", args[0])]),
+                Setting::ChangedWarnings => tr(locale, "setting-changed-warnings", &[("warnings", args[0])]),
+                Setting::CurrentEditPolicy | Setting::ChangedEditPolicy => {
+                    let explanation = tr(locale, &format!("setting-edit-{}", args[0]), &[("window", args[1])]);
+                    let key = if let Setting::CurrentEditPolicy = setting { "setting-current-edit-policy" } else { "setting-changed-edit-policy" };
+                    tr(locale, key, &[("policy", args[0]), ("explanation", &*explanation)])
+                }
+                Setting::CurrentReactionMode | Setting::ChangedReactionMode => {
+                    let explanation = tr(locale, &format!("setting-reaction-{}", args[0]), &[("window", args[1])]);
+                    let key = if let Setting::CurrentReactionMode = setting { "setting-current-reaction-mode" } else { "setting-changed-reaction-mode" };
+                    tr(locale, key, &[("mode", args[0]), ("explanation", &*explanation)])
+                }
+                Setting::CurrentMarkdown => tr(locale, "setting-current-markdown", &[
+                    ("code", &*tr(locale, &format!("state-{}", args[0]), &[])),
+                    ("spoilers", &*tr(locale, &format!("state-{}", args[1]), &[])),
+                    ("urls", &*tr(locale, &format!("state-{}", args[2]), &[])),
+                ]),
+                Setting::ChangedMarkdown => tr(locale, "setting-changed-markdown", &[
+                    ("option", &*tr(locale, &format!("markdown-option-{}", args[0]), &[])),
+                    ("state", &*tr(locale, &format!("state-{}", args[1]), &[])),
+                ]),
+                Setting::ChangedRetract => tr(locale, "setting-changed-retract", &[("window", args[0])]),
+                Setting::CurrentLanguage => tr(locale, "setting-current-language", &[("language", args[0]), ("available", args[1])]),
+                Setting::ChangedLanguage => tr(locale, "setting-changed-language", &[("language", args[0])]),
+                Setting::UnknownLanguage => tr(locale, "setting-unknown-language", &[("available", args[0])]),
+                Setting::CurrentTemplate => {
+                    // Fields left empty use the default embed
+                    let default = tr(locale, "template-default", &[]);
+                    let or_default = |v: &str| if v.is_empty() { default.clone() } else { format!("`{}`", v) };
+                    tr(locale, "setting-current-template", &[
+                        ("embed", args[0]),
+                        ("shown", args[1]),
+                        ("color", &*or_default(args[2])),
+                        ("title", &*or_default(args[3])),
+                        ("description", &*or_default(args[4])),
+                        ("placeholders", args[5]),
+                    ])
+                }
+                Setting::CurrentNotices | Setting::ChangedNotices => {
+                    // Channel notices without a lifetime are never deleted
+                    let explanation = match (args[0], args[1]) {
+                        ("channel", "") => tr(locale, "setting-notice-kept", &[]),
+                        (mode, lifetime) => tr(locale, &format!("setting-notice-{}", mode), &[("lifetime", lifetime)]),
+                    };
+                    let key = if let Setting::CurrentNotices = setting { "setting-current-notices" } else { "setting-changed-notices" };
+                    tr(locale, key, &[("mode", args[0]), ("explanation", &*explanation)])
+                }
+                Setting::ChangedTemplate => tr(locale, "setting-changed-template", &[("embed", args[0]), ("field", args[1])]),
+                Setting::ResetTemplate => tr(locale, "setting-reset-template", &[("embed", args[0])]),
+                Setting::UnknownTemplate => tr(locale, "setting-unknown-template", &[("available", args[0])]),
+                Setting::InvalidTemplate => args[0].to_string(),
+                _ => panic!("Specified embed with argurments when it shouldn't have been [settings]"),
+            }   
+        } else {
+            let key = match setting {
+                Setting::NoChannel => "setting-no-channel",
+                Setting::Malformed => "setting-malformed",
+                Setting::EnabledGlobal => "setting-enabled-global",
+                Setting::DisabledGlobal => "setting-disabled-global",
+                Setting::DisabledLogChannel => "setting-disabled-log-channel",
+                Setting::EnabledLogContent => "setting-enabled-log-content",
+                Setting::DisabledLogContent => "setting-disabled-log-content",
+                Setting::NoUser => "setting-no-user",
+                Setting::NoRole => "setting-no-role",
+                Setting::EnabledRecordExempt => "setting-enabled-record-exempt",
+                Setting::DisabledRecordExempt => "setting-disabled-record-exempt",
+                Setting::WhitelistMode => "setting-whitelist-mode",
+                Setting::BlacklistMode => "setting-blacklist-mode",
+                Setting::DisabledRetract => "setting-disabled-retract",
+                Setting::NoLogChannel => "setting-no-log-channel",
+                Setting::EnabledDms => "setting-enabled-dms",
+                Setting::DisabledDms => "setting-disabled-dms",
+                Setting::EnabledLinks => "setting-enabled-links",
+                Setting::DisabledLinks => "setting-disabled-links",
+                Setting::EnabledEmoji => "setting-enabled-emoji",
+                Setting::DisabledEmoji => "setting-disabled-emoji",
+                Setting::EnabledReplyContext => "setting-enabled-reply-context",
+                Setting::DisabledReplyContext => "setting-disabled-reply-context",
+                _ => panic!("Specified embed was not provided with arguments [settings]"),
+            };
+            tr(locale, key, &[])
+        }
+    );
+    e
 }
 
 pub async fn setting(ctx: &Context, msg: &Message, setting: Setting, args: &[&str]) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
-        m.embed(|e| {
-            e.color(SETTINGS_COLOR);
-            e.description(
-                if !args.is_empty() {              
-                    match setting {
-                        Setting::ChangedPrefix => format!("Changed the command prefix to: {}", args[0]),
-                        Setting::CurrentPrefix => format!("The command prefix is: {}", args[0]),
-                        Setting::AddedChannel => format!("Added `{}` to the channel whitelist", args[0]),
-                        Setting::RemovedChannel => format!("Removed `{}` from the channel whitelist", args[0]),
-                        _ => panic!("Specified embed with argurments when it shouldn't have been [settings]"),
-                    }   
-                } else {
-                    match setting {
-                        Setting::NoChannel => "Specified argument was not a channel.",
-                        Setting::Malformed => "Specified argument was malformed.",
-                        Setting::EnabledGlobal => "This guild will now use the global dataset.",
-                        Setting::DisabledGlobal => "This guild will no longer use the global dataset",
-                        _ => panic!("Specified embed was not provided with arguments [settings]"),
-                    }.to_string()
-                }
-            );
-            e
-        });
+        m.set_embed(setting_embed(&locale, &setting, args));
         m
     }).await);
 }
 
 pub async fn list_settings(ctx: &Context, msg: &Message) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
     check_msg(msg.channel_id.send_message(ctx, |m| m.embed(|e| {
-        e.title("Settings");
+        e.title(tr(&locale, "settings-title", &[]));
         e.color(SETTINGS_COLOR);
-        e.description("- **Prefix** Change the command prefix.
-        - **Whitelist** Add or remove channels to the whitelist.
-        - **Global** Toggle use of the cross-server dataset.");
+        e.description(tr(&locale, "settings-description", &[]));
         e
     })).await);
 }
 
-pub async fn whitelisted(ctx: &Context, msg: &Message) {
+pub async fn whitelisted_embed(ctx: &Context, guild_id: &u64) -> CreateEmbed {
+    let locale = guild_locale(ctx, Some(*guild_id)).await;
     let data = ctx.data.read().await;
     let database = data.get::<Database>().expect("Expected database in TypeMap.");
 
-    let channel_table = &format!("channels_{}", msg.guild_id.unwrap());
+    let channel_table = &format!("channels_{}", guild_id);
     let channel_ids = database.get_all_rows(channel_table, "id").await;
+    let blacklist = database.retrieve_bool("guild_settings", "blacklist", "id", guild_id).await;
     let mut desc: String;
-    if channel_ids.is_empty() {
-        desc = "No channels have been whitelisted.\nDo /settings whitelist #channel".to_string();
+    if channel_ids.is_empty() && blacklist {
+        desc = tr(&locale, "whitelist-empty-blacklist", &[]);
+    } else if channel_ids.is_empty() {
+        desc = tr(&locale, "whitelist-empty", &[]);
     } else {
-        desc = "Currently whitelisted channels:".to_string();
+        if blacklist {
+            desc = tr(&locale, "whitelist-blacklist-header", &[]);
+        } else {
+            desc = tr(&locale, "whitelist-header", &[]);
+        }
         for channel_id in channel_ids {
             // ChannelId(channel_id).name(&ctx.cache);
             desc.push_str(&format!("\n<#{}>", channel_id))
         }
     }
 
+    let mut e = CreateEmbed::default();
+    e.color(SETTINGS_COLOR);
+    e.description(desc);
+    e
+}
+
+pub async fn whitelisted(ctx: &Context, msg: &Message) {
+    let embed = whitelisted_embed(ctx, &msg.guild_id.unwrap().0).await;
+    check_msg(msg.channel_id.send_message(ctx, |m| m.set_embed(embed)).await);
+}
+
+
+
+pub async fn exempted(ctx: &Context, msg: &Message) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected database in TypeMap.");
+
+    let guild_id = msg.guild_id.unwrap();
+    let mut user_ids = Vec::new();
+    let mut role_ids = Vec::new();
+    let user_table = &format!("exempt_users_{}", guild_id);
+    if database.table_exists(user_table).await {
+        user_ids = database.get_all_rows(user_table, "id").await;
+    }
+    let role_table = &format!("exempt_roles_{}", guild_id);
+    if database.table_exists(role_table).await {
+        role_ids = database.get_all_rows(role_table, "id").await;
+    }
+    let mut desc: String;
+    if user_ids.is_empty() && role_ids.is_empty() {
+        desc = tr(&locale, "exempt-empty", &[]);
+    } else {
+        desc = tr(&locale, "exempt-header", &[]);
+        for user_id in user_ids {
+            desc.push_str(&format!("\n<@{}>", user_id))
+        }
+        for role_id in role_ids {
+            desc.push_str(&format!("\n<@&{}>", role_id))
+        }
+    }
+
     check_msg(msg.channel_id.send_message(ctx, |m| m.embed(|e| {
         e.color(SETTINGS_COLOR);
         e.description(desc);
@@ -198,53 +409,445 @@ pub async fn whitelisted(ctx: &Context, msg: &Message) {
 
 
 
-pub async fn stunlock(ctx: &Context, msg: &FauxMessage, duration: u64, streak: u64) {
-    temp_msg(ctx, 10, msg.channel_id.send_message(ctx, |m| {
+async fn guild_template(ctx: &Context, guild_id: u64, kind: TemplateKind) -> Template {
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    template::get(database, guild_id, kind).await.unwrap_or_default()
+}
+
+pub async fn stunlock(ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, duration: u64, streak: u64, penalty: &Penalty) {
+    let custom = guild_template(ctx, guild_id, TemplateKind::Stunlock).await;
+    if !custom.shown {
+        return;
+    }
+
+    let locale = guild_locale(ctx, Some(guild_id)).await;
+    let user = format!("<@{}>", offender.id.0);
+    let duration = seconds_to_string(duration);
+    let streak = to_string(streak);
+    let moderator = match penalty.moderator() {
+        0 => String::new(),
+        id => format!("<@{}>", id),
+    };
+    let reason = match penalty {
+        Penalty::Automatic { .. } => tr(&locale, "reason-automatic", &[]),
+        Penalty::Manual { .. } => reason_or_default(&locale, penalty),
+        Penalty::Reaction { emoji } => tr(&locale, "reason-reaction", &[("emoji", emoji.as_str())]),
+    };
+    let values = [
+        ("user", &*user),
+        ("duration", &*duration),
+        ("streak", &*streak),
+        ("moderator", &*moderator),
+        ("reason", &*reason),
+        ("channel", &*format!("<#{}>", channel_id.0)),
+    ];
+
+    let title = match custom.title.as_str() {
+        "" => tr(&locale, "stunlock-title", &[]),
+        t => template::render(t, &values),
+    };
+    let desc = match custom.description.as_str() {
+        "" => default_stunlock_description(&locale, &user, &duration, &streak, &moderator, penalty),
+        d => template::render(d, &values),
+    };
+    let color = custom.color.map_or(ERROR_COLOR, template::color);
+
+    // Placeholders like the reason can expand past the limits the templates were validated against
+    let mut e = CreateEmbed::default();
+    e.color(color);
+    e.title(truncate(&title, TITLE_LIMIT));
+    e.description(truncate(&desc, DESCRIPTION_LIMIT));
+    e.thumbnail("https://i.imgur.com/IEZKNZE.png");
+    e.field("\u{200B}", tr(&locale, "stunlock-why", &[]), true);
+    notice(ctx, guild_id, channel_id, offender, e).await;
+}
+
+fn default_stunlock_description(locale: &str, user: &str, duration: &str, streak: &str, moderator: &str, penalty: &Penalty) -> String {
+    match penalty {
+        Penalty::Automatic { .. } => tr(locale, "stunlock-automatic", &[("user", user), ("duration", duration), ("streak", streak)]),
+        Penalty::Manual { .. } => tr(locale, "stunlock-manual", &[
+            ("user", user),
+            ("duration", duration),
+            ("streak", streak),
+            ("moderator", moderator),
+            ("reason", &*reason_or_default(locale, penalty)),
+        ]),
+        Penalty::Reaction { emoji } => tr(locale, "stunlock-reaction", &[("user", user), ("duration", duration), ("emoji", emoji.as_str())]),
+    }
+}
+
+pub async fn warning(ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, remaining: u64) {
+    let locale = guild_locale(ctx, Some(guild_id)).await;
+    let mut e = CreateEmbed::default();
+    e.color(WARNING_COLOR);
+    e.title(tr(&locale, "warning-title", &[]));
+    e.description(tr(&locale, "warning-description", &[("user", &*format!("<@{}>", offender.id.0)), ("remaining", &*to_string(remaining))]));
+    e.field("\u{200B}", tr(&locale, "warning-why", &[]), true);
+    notice(ctx, guild_id, channel_id, offender, e).await;
+}
+
+// Sends a stunlock or warning notice wherever the guild wants them
+async fn notice(ctx: &Context, guild_id: u64, channel_id: ChannelId, offender: &User, embed: CreateEmbed) {
+    let (mode, lifetime) = {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        notices::settings(database, guild_id).await
+    };
+
+    match mode {
+        NoticeMode::Channel => {
+            let result = channel_id.send_message(ctx, |m| m.set_embed(embed)).await;
+            if lifetime == 0 {
+                check_msg(result);
+            } else {
+                temp_msg(ctx, lifetime, result).await;
+            }
+        }
+        // Violations are already logged, so there's nothing else to send
+        NoticeMode::Log => {}
+        NoticeMode::Dm => direct(ctx, guild_id, Some(channel_id), offender, embed).await,
+    }
+}
+
+// DMs a user unless they or the guild opted out of DMs
+// Anything that can't be delivered is instead mentioned briefly in the channel, when there is one
+async fn direct(ctx: &Context, guild_id: u64, channel_id: Option<ChannelId>, user: &User, embed: CreateEmbed) {
+    let allowed = {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        database.retrieve_bool("guild_settings", "direct_messages", "id", &guild_id).await
+            && !dms::is_opted_out(database, user.id.0).await
+    };
+
+    if allowed {
+        match user.direct_message(ctx, |m| m.set_embed(embed.clone())).await {
+            Ok(_) => return,
+            // Usually the user has DMs from server members closed
+            Err(why) => {
+                println!("Error sending DM: {:?}", why);
+                metrics::http_error("direct_message");
+                let data = ctx.data.read().await;
+                let database = data.get::<Database>().expect("Expected Database in TypeMap");
+                stats::increment(database, guild_id, channel_id.map_or(0, |c| c.0), Counter::DmFailures).await;
+            }
+        }
+    }
+
+    if let Some(channel_id) = channel_id {
+        temp_msg(ctx, 10, channel_id.send_message(ctx, |m| {
+            m.content(format!("<@{}>", user.id.0));
+            m.set_embed(embed)
+        }).await).await;
+    }
+}
+
+pub struct GuildStats {
+    pub total: ChannelStats,
+    pub channels: Vec<ChannelStats>,
+    pub muted: u64,
+    pub longest_streak: Option<(u64, u64)>,
+    pub top_offenders: Vec<(u64, u64)>,
+}
+
+fn format_counters(locale: &str, c: &ChannelStats) -> String {
+    tr(locale, "stats-counters", &[
+        ("checked", &*to_string(c.checked)),
+        ("recorded", &*to_string(c.recorded)),
+        ("violations", &*to_string(c.violations)),
+        ("mutes", &*to_string(c.mutes)),
+    ])
+}
+
+pub async fn stats(ctx: &Context, msg: &Message, stats: GuildStats) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+
+    // Embeds are limited to 25 fields
+    let mut channel_fields = Vec::new();
+    for c in stats.channels.iter().take(20) {
+        let name = ChannelId(c.channel).name(&ctx.cache).await.unwrap_or_else(|| to_string(c.channel));
+        channel_fields.push((format!("#{}", name), format_counters(&locale, c)));
+    }
+
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
         m.embed(|e| {
-            e.color(ERROR_COLOR);
-            e.title("Stunlocked");
-            e.description(format!("<@{}> **was stunlocked for**: `{}`\n**Current streak**: `{}`",
-              msg.author.id.0,
-              seconds_to_string(duration),
-              streak,
-            ));
-            e.thumbnail("https://i.imgur.com/IEZKNZE.png");
-            e.field("\u{200B}", "[Why did I get stunlocked?](https://github.com/DontStarve72/Ninethousand-Eighty-Four#why-was-i-muted)", true);
+            e.title(tr(&locale, "stats-title", &[]));
+            e.color(DEFAULT_COLOR);
+            e.description(format_counters(&locale, &stats.total));
+
+            let mut status = tr(&locale, "stats-muted", &[("muted", &*to_string(stats.muted))]);
+            status.push('\n');
+            match stats.longest_streak {
+                Some((user, streak)) => status.push_str(&tr(&locale, "stats-longest-streak", &[("streak", &*to_string(streak)), ("user", &*format!("<@{}>", user))])),
+                None => status.push_str(&tr(&locale, "stats-no-streak", &[])),
+            }
+            status.push('\n');
+            status.push_str(&tr(&locale, "stats-dm-failures", &[("failures", &*to_string(stats.total.dm_failures))]));
+            e.field(tr(&locale, "stats-status", &[]), status, false);
+
+            if !stats.top_offenders.is_empty() {
+                let offenders = stats.top_offenders.iter()
+                    .enumerate()
+                    .map(|(i, (user, mutes))| format!("{}. <@{}> `{}`", i + 1, user, mutes))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                e.field(tr(&locale, "stats-top-offenders", &[]), offenders, false);
+            }
+
+            for (name, counters) in channel_fields {
+                e.field(name, counters, true);
+            }
             e
         });
         m
-    }).await).await;
+    }).await);
+}
+
+pub struct Profile {
+    pub stats: UserStats,
+    pub streak: u64,
+    pub mutes: u64,
+    pub decay_in: Option<u64>,
 }
 
-pub async fn manual_mute(ctx: &Context, msg: &Message, offender: &User) {
+pub async fn leaderboard(ctx: &Context, msg: &Message, users: &[UserStats]) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let desc = if users.is_empty() {
+        tr(&locale, "leaderboard-empty", &[])
+    } else {
+        users.iter()
+            .enumerate()
+            .map(|(i, u)| tr(&locale, "leaderboard-entry", &[
+                ("rank", &*to_string(i + 1)),
+                ("user", &*format!("<@{}>", u.user)),
+                ("original", &*to_string(u.original)),
+                ("violations", &*to_string(u.violations)),
+                ("ratio", &*format!("{:.1}", u.ratio() * 100.0)),
+            ]))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
         m.embed(|e| {
+            e.title(tr(&locale, "leaderboard-title", &[]));
             e.color(DEFAULT_COLOR);
-            e.description(format!("Manually stunlocked <@{}>.",
-              offender.id.0,
-            ));
+            e.description(desc);
             e
         });
         m
     }).await);
 }
 
-pub async fn manual_streak(ctx: &Context, msg: &Message, offender_id: &u64, streak: &u64) {
+pub async fn profile(ctx: &Context, msg: &Message, profile: Profile) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
         m.embed(|e| {
+            e.title(tr(&locale, "profile-title", &[]));
             e.color(DEFAULT_COLOR);
-            e.description(format!("Set the streak of the user <@{}> to `{}`",
-              offender_id,
-              streak,
-            ));
+            let mut desc = tr(&locale, "profile-description", &[
+              ("user", &*format!("<@{}>", profile.stats.user)),
+              ("original", &*to_string(profile.stats.original)),
+              ("violations", &*to_string(profile.stats.violations)),
+              ("ratio", &*format!("{:.1}", profile.stats.ratio() * 100.0)),
+              ("streak", &*to_string(profile.streak)),
+              ("mutes", &*to_string(profile.mutes)),
+            ]);
+            if let Some(d) = profile.decay_in {
+                desc.push('\n');
+                desc.push_str(&tr(&locale, "profile-decay", &[("duration", &*seconds_to_string(d.max(1)))]));
+            }
+            e.description(desc);
+            e
+        });
+        m
+    }).await);
+}
+
+pub enum BackfillStatus {
+    Running,
+    Done,
+    Cancelled,
+    Failed,
+}
+
+pub fn backfill_embed(locale: &str, channel_id: u64, count: u64, status: BackfillStatus) -> CreateEmbed {
+    let (color, key) = match status {
+        BackfillStatus::Running => (SETTINGS_COLOR, "backfill-running"),
+        BackfillStatus::Done => (DEFAULT_COLOR, "backfill-done"),
+        BackfillStatus::Cancelled => (DEFAULT_COLOR, "backfill-cancelled"),
+        BackfillStatus::Failed => (ERROR_COLOR, "backfill-failed"),
+    };
+
+    let mut e = CreateEmbed::default();
+    e.title(tr(locale, "backfill-title", &[]));
+    e.color(color);
+    e.description(tr(locale, key, &[("channel", &*format!("<#{}>", channel_id)), ("count", &*to_string(count))]));
+    e
+}
+
+pub enum Backfill {
+    AlreadyRunning,
+    NotRunning,
+    Cancelling,
+}
+
+pub async fn backfill(ctx: &Context, msg: &Message, backfill: Backfill) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let (color, key) = match backfill {
+        Backfill::AlreadyRunning => (ERROR_COLOR, "backfill-already-running"),
+        Backfill::NotRunning => (ERROR_COLOR, "backfill-not-running"),
+        Backfill::Cancelling => (SETTINGS_COLOR, "backfill-cancelling"),
+    };
+
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.embed(|e| {
+            e.color(color);
+            e.description(tr(&locale, key, &[]));
             e
         });
         m
     }).await);
 }
 
+pub enum Originality {
+    Original,
+    Unoriginal,
+    NotEnforced,
+    NoChannel,
+    NoText,
+}
+
+pub async fn originality(ctx: &Context, msg: &Message, originality: Originality) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let (color, key) = match originality {
+        Originality::Original => (DEFAULT_COLOR, "check-original"),
+        Originality::Unoriginal => (ERROR_COLOR, "check-unoriginal"),
+        Originality::NotEnforced => (DEFAULT_COLOR, "check-not-enforced"),
+        Originality::NoChannel => (ERROR_COLOR, "error-no-channel"),
+        Originality::NoText => (ERROR_COLOR, "error-no-message"),
+    };
+
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.embed(|e| {
+            e.color(color);
+            e.description(tr(&locale, key, &[]));
+            e
+        });
+        m
+    }).await);
+}
+
+pub async fn rate_limited(ctx: &Context, msg: &Message, seconds: u64) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    temp_msg(ctx, 10, msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.embed(|e| {
+            e.color(ERROR_COLOR);
+            e.description(tr(&locale, "error-rate-limited", &[("duration", &*seconds_to_string(seconds.max(1)))]));
+            e
+        });
+        m
+    }).await).await;
+}
+
+pub fn manual_mute_embed(locale: &str, offender: &User, duration: Option<u64>, reason: &Option<String>) -> CreateEmbed {
+    let user = format!("<@{}>", offender.id.0);
+    let mut e = CreateEmbed::default();
+    e.color(DEFAULT_COLOR);
+    e.description(match duration {
+        Some(d) => tr(locale, "manual-mute-duration", &[("user", &*user), ("duration", &*seconds_to_string(d))]),
+        None => tr(locale, "manual-mute", &[("user", &*user)]),
+    });
+    if let Some(r) = reason {
+        e.field(tr(locale, "manual-mute-reason", &[]), r, false);
+    }
+    e
+}
+
+pub async fn manual_mute(ctx: &Context, msg: &Message, offender: &User, duration: Option<u64>, reason: &Option<String>) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.set_embed(manual_mute_embed(&locale, offender, duration, reason));
+        m
+    }).await);
+}
+
+pub async fn history(ctx: &Context, msg: &Message, user_id: &u64, entries: &[HistoryEntry], page: u64, pages: u64) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let user = format!("<@{}>", user_id);
+    let mut desc: String;
+    if entries.is_empty() {
+        desc = tr(&locale, "history-empty", &[("user", &*user)]);
+    } else {
+        desc = tr(&locale, "history-header", &[("user", &*user)]);
+        for entry in entries {
+            desc.push_str("\n\n");
+            desc.push_str(&tr(&locale, "history-entry", &[("time", &*to_string(entry.time)), ("channel", &*format!("<#{}>", entry.channel))]));
+            desc.push('\n');
+
+            let kind = tr(&locale, &format!("history-kind-{}", entry.kind), &[]);
+            let streak = to_string(entry.streak);
+            if entry.duration != 0 {
+                desc.push_str(&tr(&locale, "history-kind-duration", &[("kind", &*kind), ("duration", &*seconds_to_string(entry.duration)), ("streak", &*streak)]));
+            } else {
+                desc.push_str(&tr(&locale, "history-kind", &[("kind", &*kind), ("streak", &*streak)]));
+            }
+            if entry.moderator != 0 {
+                desc.push(' ');
+                desc.push_str(&tr(&locale, "history-moderator", &[("moderator", &*format!("<@{}>", entry.moderator))]));
+            }
+            if !entry.reason.is_empty() {
+                desc.push('\n');
+                // Reasons are shortened so one long reason doesn't push the rest of the page out
+                desc.push_str(&tr(&locale, "history-reason", &[("reason", &*truncate(&entry.reason, HISTORY_REASON_LIMIT))]));
+            }
+            if !entry.hash.is_empty() {
+                desc.push('\n');
+                desc.push_str(&tr(&locale, "history-hash", &[("hash", &*entry.hash)]));
+            }
+        }
+    }
+    let footer = tr(&locale, "history-page", &[("page", &*to_string(page)), ("pages", &*to_string(pages))]);
+
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.embed(|e| {
+            e.title(tr(&locale, "history-title", &[]));
+            e.color(DEFAULT_COLOR);
+            e.description(truncate(&desc, DESCRIPTION_LIMIT));
+            e.footer(|f| f.text(footer));
+            e
+        });
+        m
+    }).await);
+}
+
+pub fn manual_streak_embed(locale: &str, offender_id: &u64, streak: &u64) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.color(DEFAULT_COLOR);
+    e.description(tr(locale, "manual-streak", &[
+      ("user", &*format!("<@{}>", offender_id)),
+      ("streak", &*to_string(*streak)),
+    ]));
+    e
+}
+
+pub async fn manual_streak(ctx: &Context, msg: &Message, offender_id: &u64, streak: &u64) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
+        m.set_embed(manual_streak_embed(&locale, offender_id, streak));
+        m
+    }).await);
+}
+
 // ========================
 // 
 //     DIRECT MESSAGES
@@ -254,20 +857,81 @@ pub async fn manual_streak(ctx: &Context, msg: &Message, offender_id: &u64, stre
 
 
 pub async fn unmute(ctx: &Context, user: &User, guild_id: &u64) {
+    let custom = guild_template(ctx, *guild_id, TemplateKind::Unmute).await;
+    if !custom.shown {
+        return;
+    }
+
+    let locale = guild_locale(ctx, Some(*guild_id)).await;
     let guild_name = GuildId(*guild_id).name(&ctx.cache).await;
-    check_msg(user.direct_message(ctx, |m| {
+    let mention = format!("<@{}>", user.id.0);
+    let values = [
+        ("user", &*mention),
+        ("guild", guild_name.as_deref().unwrap_or("")),
+    ];
+
+    let desc = match (custom.description.as_str(), &guild_name) {
+        ("", Some(name)) => tr(&locale, "unmute-guild", &[("guild", &**name)]),
+        ("", None) => tr(&locale, "unmute", &[]),
+        (d, _) => truncate(&template::render(d, &values), DESCRIPTION_LIMIT),
+    };
+    let color = custom.color.map_or(DEFAULT_COLOR, template::color);
+
+    let mut e = CreateEmbed::default();
+    e.color(color);
+    // The default unmute embed has no title
+    if !custom.title.is_empty() {
+        e.title(truncate(&template::render(&custom.title, &values), TITLE_LIMIT));
+    }
+    e.description(desc);
+
+    // Falls back to where the user was last stunlocked
+    let channel_id = {
+        let data = ctx.data.read().await;
+        let database = data.get::<Database>().expect("Expected Database in TypeMap");
+        history::last_channel(database, *guild_id, user.id.0).await.map(ChannelId)
+    };
+    direct(ctx, *guild_id, channel_id, user, e).await;
+}
+
+pub async fn dm_preference(ctx: &Context, msg: &Message, opted_out: bool) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
+    let key = if opted_out { "dms-disabled" } else { "dms-enabled" };
+    check_msg(msg.channel_id.send_message(ctx, |m| {
+        m.reference_message(msg);
         m.embed(|e| {
-            e.color(DEFAULT_COLOR);
-            match guild_name {
-              Some(name) => e.description(format!("**Your stunlock in** `{}` **has ended**.", name)),
-              None => e.description("Your stunlock has ended."),
-            };
+            e.color(SETTINGS_COLOR);
+            e.description(tr(&locale, key, &[]));
             e
         });
         m
     }).await);
 }
 
+pub async fn stunlock_dm(ctx: &Context, user: &User, guild_id: &u64, channel_id: ChannelId, duration: u64, penalty: &Penalty) {
+    let locale = guild_locale(ctx, Some(*guild_id)).await;
+    let guild_name = GuildId(*guild_id).name(&ctx.cache).await;
+    let mut e = CreateEmbed::default();
+    e.color(ERROR_COLOR);
+    match guild_name {
+      Some(name) => e.title(tr(&locale, "stunlock-dm-title-guild", &[("guild", &*name)])),
+      None => e.title(tr(&locale, "stunlock-dm-title", &[])),
+    };
+    e.description(tr(&locale, "stunlock-dm-description", &[
+      ("duration", &*seconds_to_string(duration)),
+      ("moderator", &*format!("<@{}>", penalty.moderator())),
+      ("reason", &*reason_or_default(&locale, penalty)),
+    ]));
+    direct(ctx, *guild_id, Some(channel_id), user, e).await;
+}
+
+fn reason_or_default(locale: &str, penalty: &Penalty) -> String {
+    match penalty.reason() {
+        "" => tr(locale, "no-reason", &[]),
+        r => r.to_string(),
+    }
+}
+
 
 
 // =======================
@@ -280,12 +944,13 @@ pub async fn unmute(ctx: &Context, user: &User, guild_id: &u64) {
 
 pub async fn no_user (ctx: &Context, msg: &Message) {
     let prefix = get_prefix(ctx, msg).await;
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
 
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
         m.embed(|e| {
             e.color(ERROR_COLOR);
-            e.description(format!("You must specify a user. See `{}help` for examples.", prefix));
+            e.description(tr(&locale, "error-no-user", &[("prefix", &*prefix)]));
             e
         });
         m
@@ -294,26 +959,287 @@ pub async fn no_user (ctx: &Context, msg: &Message) {
 
 pub async fn no_int (ctx: &Context, msg: &Message) {
     let prefix = get_prefix(ctx, msg).await;
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
     
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
         m.embed(|e| {
             e.color(ERROR_COLOR);
-            e.description(format!("You must specify a number. See `{}help` for examples.", prefix));
+            e.description(tr(&locale, "error-no-int", &[("prefix", &*prefix)]));
             e
         });
         m
     }).await);
 }
 
+pub fn streak_bad_size_embed(locale: &str) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.color(ERROR_COLOR);
+    e.description(tr(locale, "error-streak-size", &[]));
+    e
+}
+
 pub async fn streak_bad_size (ctx: &Context, msg: &Message) {
+    let locale = guild_locale(ctx, msg.guild_id.map(|g| g.0)).await;
     check_msg(msg.channel_id.send_message(ctx, |m| {
         m.reference_message(msg);
+        m.set_embed(streak_bad_size_embed(&locale));
+        m
+    }).await);
+}
+
+// Errors for slash commands, which are only shown to the user who ran the command
+pub fn interaction_error_embed(description: &str) -> CreateEmbed {
+    let mut e = CreateEmbed::default();
+    e.color(ERROR_COLOR);
+    e.description(description);
+    e
+}
+
+
+
+// ===================
+// 
+//     LOG MESSAGES
+// 
+// ===================
+
+
+
+pub enum LogEntry<'a> {
+    Mute {
+        user: u64,
+        channel: u64,
+        duration: u64,
+        streak: u64,
+        penalty: &'a Penalty,
+    },
+    Warning {
+        user: u64,
+        channel: u64,
+        remaining: u64,
+    },
+    Streak {
+        moderator: u64,
+        user: u64,
+        old: u64,
+        new: u64,
+    },
+    Setting {
+        moderator: u64,
+        change: SettingChange,
+    },
+    Whitelist {
+        moderator: u64,
+        channel: u64,
+        added: bool,
+    },
+}
+
+// A settings change as it's reported in the log, described in the guild's language
+pub enum SettingChange {
+    Prefix(String),
+    Global(bool),
+    Blacklist(bool),
+    Warnings(u64),
+    // Zero turns retracting off
+    Retract(u64),
+    EditPolicy(&'static str),
+    DirectMessages(bool),
+    NoticeMode(&'static str),
+    Language(String),
+    ReactionMode(&'static str),
+    // Zero turns the log channel off
+    LogChannel(u64),
+    LogContent(bool),
+    ReplyContext(bool),
+    // The option as it's named in the command, and whether it's now on
+    Markdown(&'static str, bool),
+    Links(bool),
+    Emoji(bool),
+    ResetTemplate(TemplateKind),
+    Template(TemplateKind, String),
+    // The mentioned user or role, and whether they were added
+    Exemption(String, bool),
+    RecordExempt(bool),
+}
+
+impl SettingChange {
+    pub fn describe(&self, locale: &str) -> String {
+        let toggled = |key: &str, enabled: &bool| tr(locale, &format!("{}-{}", key, if *enabled { "enabled" } else { "disabled" }), &[]);
+        match self {
+            SettingChange::Prefix(prefix) => tr(locale, "log-change-prefix", &[("prefix", prefix.as_str())]),
+            SettingChange::Global(enabled) => toggled("log-change-global", enabled),
+            SettingChange::Blacklist(true) => tr(locale, "log-change-blacklist", &[]),
+            SettingChange::Blacklist(false) => tr(locale, "log-change-whitelist", &[]),
+            SettingChange::Warnings(warnings) => tr(locale, "log-change-warnings", &[("warnings", &*to_string(warnings))]),
+            SettingChange::Retract(0) => tr(locale, "log-change-retract-off", &[]),
+            SettingChange::Retract(window) => tr(locale, "log-change-retract", &[("window", &*seconds_to_string(*window))]),
+            SettingChange::EditPolicy(policy) => tr(locale, "log-change-edit-policy", &[("policy", policy)]),
+            SettingChange::DirectMessages(enabled) => toggled("log-change-dms", enabled),
+            SettingChange::NoticeMode(mode) => tr(locale, "log-change-notice-mode", &[("mode", mode)]),
+            SettingChange::Language(code) => tr(locale, "log-change-language", &[("language", code.as_str())]),
+            SettingChange::ReactionMode(mode) => tr(locale, "log-change-reaction-mode", &[("mode", mode)]),
+            SettingChange::LogChannel(0) => tr(locale, "log-change-log-channel-off", &[]),
+            SettingChange::LogChannel(channel) => tr(locale, "log-change-log-channel", &[("channel", &*format!("<#{}>", channel))]),
+            SettingChange::LogContent(enabled) => toggled("log-change-log-content", enabled),
+            SettingChange::ReplyContext(enabled) => toggled("log-change-reply-context", enabled),
+            SettingChange::Markdown(option, enabled) => tr(locale, "log-change-markdown", &[
+                ("option", &*tr(locale, &format!("markdown-option-{}", option), &[])),
+                ("state", &*tr(locale, if *enabled { "state-on" } else { "state-off" }, &[])),
+            ]),
+            SettingChange::Links(enabled) => toggled("log-change-links", enabled),
+            SettingChange::Emoji(enabled) => toggled("log-change-emoji", enabled),
+            SettingChange::ResetTemplate(kind) => tr(locale, "log-change-template-reset", &[("embed", kind.name())]),
+            SettingChange::Template(kind, field) => tr(locale, "log-change-template", &[("embed", kind.name()), ("field", field.as_str())]),
+            SettingChange::Exemption(target, true) => tr(locale, "log-change-exemption-added", &[("target", target.as_str())]),
+            SettingChange::Exemption(target, false) => tr(locale, "log-change-exemption-removed", &[("target", target.as_str())]),
+            SettingChange::RecordExempt(enabled) => toggled("log-change-record-exempt", enabled),
+        }
+    }
+}
+
+// Posts an entry to the guild's log channel, if it has one
+pub async fn log(ctx: &Context, guild_id: &u64, entry: LogEntry<'_>) {
+    let locale = guild_locale(ctx, Some(*guild_id)).await;
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+
+    let log_channel = database.retrieve_int("guild_settings", "log_channel", "id", guild_id).await as u64;
+    if log_channel == 0 {
+        return;
+    }
+    let log_content = database.retrieve_bool("guild_settings", "log_content", "id", guild_id).await;
+    drop(data);
+
+    let (title, color, desc) = match entry {
+        LogEntry::Mute { user, channel, duration, streak, penalty } => {
+            let mut desc = tr(&locale, "log-mute", &[
+                ("user", &*format!("<@{}>", user)),
+                ("channel", &*format!("<#{}>", channel)),
+                ("duration", &*seconds_to_string(duration)),
+                ("streak", &*to_string(streak)),
+            ]);
+            desc.push('\n');
+            match penalty {
+                Penalty::Automatic { hash, content } => {
+                    desc.push_str(&tr(&locale, "log-hash", &[("hash", &*to_string(*hash))]));
+                    if log_content {
+                        desc.push('\n');
+                        desc.push_str(&tr(&locale, "log-content", &[("content", &*quote(&locale, content))]));
+                    }
+                    ("log-automatic-title", ERROR_COLOR, desc)
+                }
+                Penalty::Manual { moderator, .. } => {
+                    desc.push_str(&tr(&locale, "log-moderator", &[("moderator", &*format!("<@{}>", moderator)), ("reason", &*reason_or_default(&locale, penalty))]));
+                    ("log-manual-title", ERROR_COLOR, desc)
+                }
+                Penalty::Reaction { emoji } => {
+                    desc.push_str(&tr(&locale, "log-reaction", &[("emoji", emoji.as_str())]));
+                    ("log-reaction-title", ERROR_COLOR, desc)
+                }
+            }
+        }
+        LogEntry::Warning { user, channel, remaining } => {
+            ("log-warning-title", WARNING_COLOR, tr(&locale, "log-warning", &[
+                ("user", &*format!("<@{}>", user)),
+                ("channel", &*format!("<#{}>", channel)),
+                ("remaining", &*to_string(remaining)),
+            ]))
+        }
+        LogEntry::Streak { moderator, user, old, new } => {
+            let desc = tr(&locale, "log-streak", &[
+                ("moderator", &*format!("<@{}>", moderator)),
+                ("user", &*format!("<@{}>", user)),
+                ("old", &*to_string(old)),
+                ("new", &*to_string(new)),
+            ]);
+            if new < old {
+                ("log-pardon-title", DEFAULT_COLOR, desc)
+            } else {
+                ("log-streak-title", DEFAULT_COLOR, desc)
+            }
+        }
+        LogEntry::Setting { moderator, change } => {
+            ("log-setting-title", SETTINGS_COLOR, tr(&locale, "log-setting", &[("moderator", &*format!("<@{}>", moderator)), ("change", &*change.describe(&locale))]))
+        }
+        LogEntry::Whitelist { moderator, channel, added } => {
+            let key = if added { "log-channel-added" } else { "log-channel-removed" };
+            ("log-whitelist-title", SETTINGS_COLOR, tr(&locale, key, &[("moderator", &*format!("<@{}>", moderator)), ("channel", &*format!("<#{}>", channel))]))
+        }
+    };
+
+    check_msg(ChannelId(log_channel).send_message(ctx, |m| {
         m.embed(|e| {
-            e.color(ERROR_COLOR);
-            e.description("Specified streak must be `at least 0` or `below 16`.");
+            e.title(tr(&locale, title, &[]));
+            e.color(color);
+            e.description(desc);
             e
         });
         m
     }).await);
-}
\ No newline at end of file
+}
+
+// Quotes a message for review, keeping well below the embed description limit
+fn quote(locale: &str, content: &str) -> String {
+    let mut content: String = content.chars().take(1000).collect();
+    if content.is_empty() {
+        content = tr(locale, "log-no-content", &[]);
+    }
+
+    content.lines()
+        .map(|l| format!("> {}", l))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::locale::available;
+
+    #[test]
+    fn setting_changes_are_described_from_the_catalogs() {
+        let mut changes = vec![
+            SettingChange::Prefix("9!".to_string()),
+            SettingChange::Warnings(3),
+            SettingChange::Retract(0),
+            SettingChange::Retract(300),
+            SettingChange::EditPolicy("grace"),
+            SettingChange::NoticeMode("log"),
+            SettingChange::Language("es".to_string()),
+            SettingChange::ReactionMode("channel"),
+            SettingChange::LogChannel(0),
+            SettingChange::LogChannel(1),
+            SettingChange::ResetTemplate(TemplateKind::Stunlock),
+            SettingChange::Template(TemplateKind::Unmute, "title".to_string()),
+        ];
+        for enabled in [true, false] {
+            changes.extend([
+                SettingChange::Global(enabled),
+                SettingChange::Blacklist(enabled),
+                SettingChange::DirectMessages(enabled),
+                SettingChange::LogContent(enabled),
+                SettingChange::ReplyContext(enabled),
+                SettingChange::Markdown("code", enabled),
+                SettingChange::Markdown("spoilers", enabled),
+                SettingChange::Markdown("urls", enabled),
+                SettingChange::Links(enabled),
+                SettingChange::Emoji(enabled),
+                SettingChange::Exemption("<@1>".to_string(), enabled),
+                SettingChange::RecordExempt(enabled),
+            ]);
+        }
+
+        // A key missing from the catalogs is rendered as the key itself
+        for locale in available() {
+            for change in &changes {
+                let described = change.describe(locale);
+                for prefix in ["log-change-", "markdown-option-", "state-"] {
+                    assert!(!described.contains(prefix), "[{}] {}", locale, described);
+                }
+            }
+        }
+        assert_eq!(SettingChange::Warnings(3).describe("es"), "Fijó las advertencias antes de silenciar en `3`");
+    }
+}
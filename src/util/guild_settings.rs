@@ -0,0 +1,70 @@
+use sqlx::Row;
+
+use crate::events::on_message::EditPolicy;
+
+use super::{
+    database::DatabaseTool,
+    misc::Normalization,
+    reactions::ReactionMode,
+    metrics,
+};
+
+// The settings consulted for every message and reaction, loaded in one query instead of one per column
+pub struct GuildSettings {
+    pub guild_id: u64,
+    pub global: bool,
+    pub blacklist: bool,
+    pub record_exempt: bool,
+    pub retract_window: u64,
+    pub edit_policy: EditPolicy,
+    pub edit_window: u64,
+    pub reaction_mode: ReactionMode,
+    pub reaction_window: u64,
+    pub reply_context: bool,
+    pub link_fingerprints: bool,
+    pub emoji_fingerprints: bool,
+    pub normalization: Normalization,
+}
+
+const COLUMNS: &str = "global, blacklist, record_exempt, retract_window, edit_policy, edit_window, reaction_mode, reaction_window, \
+    reply_context, link_fingerprints, emoji_fingerprints, code_verbatim, strip_spoilers, separate_urls";
+
+pub async fn load(database: &DatabaseTool, guild_id: u64) -> GuildSettings {
+    let _timer = metrics::query_timer("guild_settings");
+    let query = format!("SELECT {} FROM guild_settings WHERE id = {}", COLUMNS, guild_id);
+
+    let mut row = sqlx::query(query.as_str())
+    .fetch_optional(&database.pool)
+    .await
+    .expect("Could not query database [guild_settings load]");
+
+    // Settings are created when the guild is, but an event can beat that
+    if row.is_none() {
+        database.ensure_guild(&guild_id).await;
+        row = sqlx::query(query.as_str())
+        .fetch_optional(&database.pool)
+        .await
+        .expect("Could not query database [guild_settings load]");
+    }
+    let row = row.expect("Guild settings missing after being created [guild_settings load]");
+
+    GuildSettings {
+        guild_id,
+        global: row.get::<bool, &str>("global"),
+        blacklist: row.get::<bool, &str>("blacklist"),
+        record_exempt: row.get::<bool, &str>("record_exempt"),
+        retract_window: row.get::<i64, &str>("retract_window") as u64,
+        edit_policy: EditPolicy::from(row.get::<i64, &str>("edit_policy")),
+        edit_window: row.get::<i64, &str>("edit_window") as u64,
+        reaction_mode: ReactionMode::from(row.get::<i64, &str>("reaction_mode")),
+        reaction_window: row.get::<i64, &str>("reaction_window") as u64,
+        reply_context: row.get::<bool, &str>("reply_context"),
+        link_fingerprints: row.get::<bool, &str>("link_fingerprints"),
+        emoji_fingerprints: row.get::<bool, &str>("emoji_fingerprints"),
+        normalization: Normalization {
+            code_verbatim: row.get::<bool, &str>("code_verbatim"),
+            strip_spoilers: row.get::<bool, &str>("strip_spoilers"),
+            separate_urls: row.get::<bool, &str>("separate_urls"),
+        },
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sqlx::Row;
+
+use super::{
+    database::{DatabaseTool, INTEGER, TEXT},
+    misc::to_string,
+    metrics,
+};
+
+pub const HISTORY_PAGE_SIZE: u64 = 10;
+
+pub enum HistoryKind {
+    Automatic,
+    Manual,
+    Pardon,
+    Warning,
+    Reaction,
+}
+
+impl HistoryKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HistoryKind::Automatic => "automatic",
+            HistoryKind::Manual => "manual",
+            HistoryKind::Pardon => "pardon",
+            HistoryKind::Warning => "warning",
+            HistoryKind::Reaction => "reaction",
+        }
+    }
+}
+
+pub struct HistoryEntry {
+    pub time: u64,
+    pub channel: u64,
+    pub kind: String,
+    pub duration: u64,
+    pub streak: u64,
+    pub hash: String,
+    pub moderator: u64,
+    pub reason: String,
+}
+
+pub struct HistoryRecord<'a> {
+    pub guild: u64,
+    pub user: u64,
+    pub channel: u64,
+    pub kind: HistoryKind,
+    pub duration: u64,
+    pub streak: u64,
+    pub hash: Option<u128>,
+    pub moderator: u64,
+    pub reason: &'a str,
+}
+
+// The history table is append-only, rows are never updated or deleted
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("history").await {
+        database.create_table("history",
+            &["time", "guild", "user", "channel", "kind", "duration", "streak", "hash", "moderator", "reason"],
+            &[INTEGER, INTEGER, INTEGER, INTEGER, TEXT, INTEGER, INTEGER, TEXT, INTEGER, TEXT],
+        ).await;
+    }
+}
+
+pub async fn record(database: &DatabaseTool, record: HistoryRecord<'_>) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let hash = match record.hash {
+        Some(h) => to_string(h),
+        None => String::new(),
+    };
+
+    database.insert_row("history", &[
+        &to_string(now),
+        &to_string(record.guild),
+        &to_string(record.user),
+        &to_string(record.channel),
+        record.kind.as_str(),
+        &to_string(record.duration),
+        &to_string(record.streak),
+        &hash,
+        &to_string(record.moderator),
+        record.reason,
+    ]).await;
+}
+
+pub async fn count(database: &DatabaseTool, guild_id: u64, user_id: u64) -> u64 {
+    let _timer = metrics::query_timer("history_count");
+    let row: (i64,) = sqlx::query_as(format!("SELECT COUNT(*) FROM history WHERE guild = {} AND user = {}", guild_id, user_id).as_str())
+    .fetch_one(&database.pool)
+    .await
+    .expect("Could not query database [history count]");
+
+    row.0 as u64
+}
+
+// How many entries of a kind a user has had since a point in time
+pub async fn count_since(database: &DatabaseTool, guild_id: u64, user_id: u64, kind: HistoryKind, since: u64) -> u64 {
+    let _timer = metrics::query_timer("history_count_since");
+    let row: (i64,) = sqlx::query_as(format!("SELECT COUNT(*) FROM history WHERE guild = {} AND user = {} AND kind = '{}' AND time >= {}", guild_id, user_id, kind.as_str(), since).as_str())
+    .fetch_one(&database.pool)
+    .await
+    .expect("Could not query database [history count_since]");
+
+    row.0 as u64
+}
+
+// The channel a user was last stunlocked in, used when the bot has to reach them outside of a violation
+pub async fn last_channel(database: &DatabaseTool, guild_id: u64, user_id: u64) -> Option<u64> {
+    let _timer = metrics::query_timer("history_last_channel");
+    let row: Option<(i64,)> = sqlx::query_as(format!("SELECT channel FROM history WHERE guild = {} AND user = {} AND duration > 0 AND channel != 0 ORDER BY time DESC LIMIT 1", guild_id, user_id).as_str())
+    .fetch_optional(&database.pool)
+    .await
+    .expect("Could not query database [history last_channel]");
+
+    row.map(|r| r.0 as u64)
+}
+
+// Newest entries first, page is zero indexed
+pub async fn page(database: &DatabaseTool, guild_id: u64, user_id: u64, page: u64) -> Vec<HistoryEntry> {
+    let _timer = metrics::query_timer("history_page");
+    let result = sqlx::query(format!("SELECT time, channel, kind, duration, streak, hash, moderator, reason FROM history WHERE guild = {} AND user = {} ORDER BY time DESC LIMIT {} OFFSET {}",
+        guild_id, user_id, HISTORY_PAGE_SIZE, page.saturating_mul(HISTORY_PAGE_SIZE)).as_str())
+    .fetch_all(&database.pool)
+    .await
+    .expect("Could not query database [history page]");
+
+    result.iter()
+        .map(|r| HistoryEntry {
+            time: r.get::<i64, usize>(0) as u64,
+            channel: r.get::<i64, usize>(1) as u64,
+            kind: r.get::<String, usize>(2),
+            duration: r.get::<i64, usize>(3) as u64,
+            streak: r.get::<i64, usize>(4) as u64,
+            hash: r.get::<String, usize>(5),
+            moderator: r.get::<i64, usize>(6) as u64,
+            reason: r.get::<String, usize>(7),
+        })
+        .collect()
+}
@@ -0,0 +1,138 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+// Query parameters which only track where a link was shared from
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "dclid", "igshid", "igsh", "mc_cid", "mc_eid", "ref_src", "si"];
+
+// Every link in a message, in canonical form
+pub fn extract(content: &str) -> Vec<String> {
+    lazy_static! {
+        static ref URL: Regex = Regex::new(r"https?://[^\s<>]+").unwrap();
+    }
+    let mut links: Vec<String> = URL.find_iter(content)
+        .filter_map(|m| canonicalize(m.as_str()))
+        .collect();
+    links.sort();
+    links.dedup();
+    links
+}
+
+// Reduces a URL to the parts that decide what it points to
+// The scheme, `www.`, fragments, tracking parameters and parameter order are dropped, and known short forms are expanded
+pub fn canonicalize(url: &str) -> Option<String> {
+    // Trailing punctuation is usually part of the sentence rather than the link
+    let url = url.trim_end_matches(['.', ',', ')', '!', '?', ';', ':', '\'', '"', '>', '|', '*', '_', '~']);
+    let rest = url.split_once("://").map(|(_, r)| r)?;
+    let rest = rest.split('#').next().unwrap_or("");
+
+    let (location, query) = match rest.split_once('?') {
+        Some((l, q)) => (l, q),
+        None => (rest, ""),
+    };
+    let (host, path) = match location.split_once('/') {
+        Some((h, p)) => (h, p),
+        None => (location, ""),
+    };
+
+    // Credentials and ports never change what's being linked
+    let host = host.rsplit('@').next().unwrap_or(host);
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    let host = host.trim_start_matches("www.").trim_start_matches("m.").to_string();
+    if host.is_empty() {
+        return None;
+    }
+
+    let mut params: Vec<(String, String)> = query.split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.split_once('=') {
+            Some((k, v)) => (k.to_lowercase(), v.to_string()),
+            None => (p.to_lowercase(), String::new()),
+        })
+        .filter(|(k, _)| !k.starts_with("utm_") && !TRACKING_PARAMS.contains(&k.as_str()))
+        .collect();
+    let mut path = path.trim_end_matches('/').to_string();
+    let mut host = host;
+
+    match host.as_str() {
+        // youtu.be/x, youtube.com/shorts/x and youtube.com/embed/x are all youtube.com/watch?v=x
+        "youtu.be" | "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            let id = if host == "youtu.be" {
+                Some(path.clone())
+            } else if let Some(id) = path.strip_prefix("shorts/").or_else(|| path.strip_prefix("embed/")).or_else(|| path.strip_prefix("live/")) {
+                Some(id.to_string())
+            } else if path == "watch" {
+                params.iter().find(|(k, _)| k == "v").map(|(_, v)| v.clone())
+            } else {
+                None
+            };
+
+            if let Some(id) = id {
+                host = "youtube.com".to_string();
+                path = "watch".to_string();
+                // Timestamps and playlists still point to the same video
+                params = vec![("v".to_string(), id)];
+            }
+        }
+        "x.com" | "twitter.com" | "mobile.twitter.com" | "fxtwitter.com" | "vxtwitter.com" | "fixupx.com" => {
+            host = "twitter.com".to_string();
+            params.clear();
+        }
+        "redd.it" => {
+            host = "reddit.com".to_string();
+            path = format!("comments/{}", path);
+        }
+        "old.reddit.com" | "new.reddit.com" => {
+            host = "reddit.com".to_string();
+        }
+        _ => {}
+    }
+
+    params.sort();
+    let query: Vec<String> = params.iter()
+        .map(|(k, v)| if v.is_empty() { k.clone() } else { format!("{}={}", k, v) })
+        .collect();
+
+    let mut canonical = host;
+    if !path.is_empty() {
+        canonical.push('/');
+        canonical.push_str(&path);
+    }
+    if !query.is_empty() {
+        canonical.push('?');
+        canonical.push_str(&query.join("&"));
+    }
+    Some(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheme_and_www_are_dropped() {
+        assert_eq!(canonicalize("https://www.example.com/page/").unwrap(), "example.com/page");
+        assert_eq!(canonicalize("http://Example.com:8080/page#top").unwrap(), "example.com/page");
+        assert!(canonicalize("example.com/page").is_none());
+    }
+
+    #[test]
+    fn tracking_parameters_are_dropped() {
+        assert_eq!(canonicalize("https://example.com/a?utm_source=x&b=2&fbclid=y&a=1").unwrap(), "example.com/a?a=1&b=2");
+        assert_eq!(canonicalize("https://example.com/a?si=abc").unwrap(), "example.com/a");
+    }
+
+    #[test]
+    fn short_forms_are_expanded() {
+        let video = "youtube.com/watch?v=x";
+        assert_eq!(canonicalize("https://youtu.be/x").unwrap(), video);
+        assert_eq!(canonicalize("https://www.youtube.com/watch?v=x&t=3").unwrap(), video);
+        assert_eq!(canonicalize("https://m.youtube.com/shorts/x?feature=share").unwrap(), video);
+        assert_eq!(canonicalize("https://x.com/user/status/1?s=20").unwrap(), "twitter.com/user/status/1");
+        assert_eq!(canonicalize("https://redd.it/abc").unwrap(), "reddit.com/comments/abc");
+    }
+
+    #[test]
+    fn trailing_punctuation_and_duplicates_are_ignored() {
+        assert_eq!(extract("see https://youtu.be/x, or (https://youtube.com/watch?v=x&t=3)."), vec!["youtube.com/watch?v=x"]);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serenity::client::Context;
+
+use crate::Database;
+
+// Every locale the bot ships with, English is the reference and the fallback
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../../locales/en.ftl")),
+    ("es", include_str!("../../locales/es.ftl")),
+];
+pub const DEFAULT_LOCALE: &str = "en";
+
+lazy_static! {
+    static ref CATALOG: HashMap<&'static str, HashMap<String, String>> = LOCALES.iter()
+        .map(|(code, source)| (*code, parse(source)))
+        .collect();
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\s*\$([a-z_]+)\s*\}").unwrap();
+}
+
+// Reads the subset of Fluent the catalogs use: `key = value`, with indented lines continuing the value and `#` comments
+fn parse(source: &str) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+
+    for line in source.lines() {
+        // Continuation lines are indented, blank lines may separate paragraphs within a value
+        if line.starts_with(' ') || line.is_empty() {
+            if let Some((_, value)) = &mut current {
+                value.push(line.trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = current.take() {
+            messages.insert(key, join(value));
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            let lines = if value.is_empty() { Vec::new() } else { vec![value.to_string()] };
+            current = Some((key.trim().to_string(), lines));
+        }
+    }
+    if let Some((key, value)) = current.take() {
+        messages.insert(key, join(value));
+    }
+
+    messages
+}
+
+fn join(lines: Vec<String>) -> String {
+    lines.join("\n").trim().to_string()
+}
+
+pub fn is_available(code: &str) -> bool {
+    CATALOG.contains_key(code)
+}
+
+pub fn available() -> Vec<&'static str> {
+    LOCALES.iter().map(|(code, _)| *code).collect()
+}
+
+// Looks up a message and fills in its `{ $name }` placeholders
+// Falls back to English if the locale doesn't have the message, and to the key itself if English doesn't either
+pub fn tr(locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let template = CATALOG.get(locale)
+        .and_then(|m| m.get(key))
+        .or_else(|| CATALOG.get(DEFAULT_LOCALE).and_then(|m| m.get(key)));
+
+    let template = match template {
+        Some(t) => t,
+        None => return key.to_string(),
+    };
+
+    PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+        let name = &caps[1];
+        args.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.to_string())
+            .unwrap_or_else(|| caps[0].to_string())
+    }).to_string()
+}
+
+// The locale a guild picked, or English outside of guilds
+pub async fn guild_locale(ctx: &Context, guild_id: Option<u64>) -> String {
+    let guild_id = match guild_id {
+        Some(id) => id,
+        None => return DEFAULT_LOCALE.to_string(),
+    };
+
+    let data = ctx.data.read().await;
+    let database = data.get::<Database>().expect("Expected Database in TypeMap");
+    let locale = database.retrieve_str("guild_settings", "language", "id", &guild_id).await;
+    if is_available(&locale) {
+        locale
+    } else {
+        DEFAULT_LOCALE.to_string()
+    }
+}
+
+fn placeholders(template: &str) -> HashSet<String> {
+    PLACEHOLDER.captures_iter(template).map(|c| c[1].to_string()).collect()
+}
+
+// Run at startup, so a locale missing a message or using placeholders English doesn't provide is caught before anyone sees it
+// Returns a description of every problem found
+pub fn validate() -> Vec<String> {
+    let mut problems = Vec::new();
+    let reference = &CATALOG[DEFAULT_LOCALE];
+
+    for (code, messages) in CATALOG.iter() {
+        for (key, template) in reference {
+            match messages.get(key) {
+                Some(translated) => {
+                    let unknown: Vec<String> = placeholders(translated).difference(&placeholders(template)).cloned().collect();
+                    if !unknown.is_empty() {
+                        problems.push(format!("[{}] `{}` uses unknown placeholders: {}", code, key, unknown.join(", ")));
+                    }
+                }
+                None => problems.push(format!("[{}] missing `{}`", code, key)),
+            }
+        }
+        for key in messages.keys() {
+            if !reference.contains_key(key) {
+                problems.push(format!("[{}] `{}` isn't in the {} locale", code, key, DEFAULT_LOCALE));
+            }
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogs_have_the_same_keys() {
+        let reference: HashSet<&String> = CATALOG[DEFAULT_LOCALE].keys().collect();
+        for code in available() {
+            let keys: HashSet<&String> = CATALOG[code].keys().collect();
+            assert_eq!(keys, reference, "`{}` and `{}` have different keys", code, DEFAULT_LOCALE);
+        }
+        assert!(validate().is_empty(), "{:?}", validate());
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        assert_eq!(tr("en", "setting-current-prefix", &[("prefix", "9!")]), "The command prefix is: 9!");
+        assert_eq!(tr("es", "setting-current-prefix", &[("prefix", "9!")]), "El prefijo de los comandos es: 9!");
+        // Missing arguments are left as they are, rather than silently dropped
+        assert_eq!(tr("en", "setting-current-prefix", &[]), "The command prefix is: { $prefix }");
+    }
+
+    #[test]
+    fn falls_back_to_the_default_locale() {
+        assert_eq!(tr("xx", "error-guild-only", &[]), tr(DEFAULT_LOCALE, "error-guild-only", &[]));
+        assert_eq!(tr("es", "no-such-message", &[]), "no-such-message");
+    }
+
+    #[test]
+    fn parses_continuation_lines_and_comments() {
+        let messages = parse("# comment\none = First\ntwo =\n    Line one\n\n    Line two\nthree = Third\n");
+        assert_eq!(messages["one"], "First");
+        assert_eq!(messages["two"], "Line one\n\nLine two");
+        assert_eq!(messages["three"], "Third");
+        assert_eq!(messages.len(), 3);
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sqlx::Row;
+
+use super::{
+    database::{DatabaseTool, INTEGER, TEXT},
+    misc::to_string,
+    metrics,
+};
+
+// Longest a guild can let authors retract their messages for
+pub const MAX_RETRACT_WINDOW: u64 = 60 * 60 * 24 * 7;
+
+// Which hash each recent message recorded, so that it can be retracted later
+pub struct RecordedMessage {
+    pub guild: u64,
+    pub user: u64,
+    pub hash: u128,
+    pub time: u64,
+}
+
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("message_hashes").await {
+        database.create_table("message_hashes", &["id", "guild", "user", "hash", "time", "expires"], &[INTEGER, INTEGER, INTEGER, TEXT, INTEGER, INTEGER]).await;
+    } else if !database.column_exists("message_hashes", "expires").await {
+        // Older mappings were kept for at most a week
+        database.add_column("message_hashes", "expires", INTEGER, Some("0")).await;
+        sqlx::query(format!("UPDATE message_hashes SET expires = time + {}", MAX_RETRACT_WINDOW).as_str())
+        .execute(&database.pool)
+        .await
+        .expect("Could not update database [messages create_table]");
+    }
+
+    if !database.index_exists("message_hashes", "message").await {
+        database.add_index("message_hashes", "message", &["id"]).await;
+    }
+    if !database.index_exists("message_hashes", "expiry").await {
+        database.add_index("message_hashes", "expiry", &["expires"]).await;
+    }
+}
+
+// Mappings are kept for the window, and pruned by the loop afterwards
+pub async fn record(database: &DatabaseTool, message_id: u64, guild_id: u64, user_id: u64, hash: u128, window: u64) {
+    let _timer = metrics::query_timer("messages_record");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    database.insert_row("message_hashes", &[&to_string(message_id), &to_string(guild_id), &to_string(user_id), &to_string(hash), &to_string(now), &to_string(now + window)]).await;
+}
+
+// Drops mappings which are too old to ever be retracted
+pub async fn prune(database: &DatabaseTool) {
+    let _timer = metrics::query_timer("messages_prune");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    sqlx::query(format!("DELETE FROM message_hashes WHERE expires < {}", now).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [messages prune]");
+}
+
+// Removes and returns the mappings for a message, one for its text and one for each new link it had
+pub async fn take(database: &DatabaseTool, message_id: u64) -> Vec<RecordedMessage> {
+    let _timer = metrics::query_timer("messages_take");
+    let rows = sqlx::query(format!("SELECT guild, user, hash, time FROM message_hashes WHERE id = {}", message_id).as_str())
+    .fetch_all(&database.pool)
+    .await
+    .expect("Could not query database [messages take]");
+
+    if rows.is_empty() {
+        return Vec::new();
+    }
+    database.delete_row("message_hashes", "id", &message_id).await;
+
+    rows.iter()
+        .filter_map(|row| Some(RecordedMessage {
+            guild: row.get::<i64, usize>(0) as u64,
+            user: row.get::<i64, usize>(1) as u64,
+            hash: row.get::<String, usize>(2).parse().ok()?,
+            time: row.get::<i64, usize>(3) as u64,
+        }))
+        .collect()
+}
+
+// Removes a hash from the dataset, making its content original again
+pub async fn retract(database: &DatabaseTool, hash: u128) {
+    let _timer = metrics::query_timer("messages_retract");
+    sqlx::query(format!("DELETE FROM global WHERE id = '{}'", hash).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [messages retract]");
+}
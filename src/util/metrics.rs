@@ -0,0 +1,137 @@
+use std::net::SocketAddr;
+
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, TextEncoder, Registry,
+    IntCounter, IntCounterVec, Histogram, HistogramVec, HistogramTimer,
+    HistogramOpts, Opts,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{error, info};
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::new();
+
+    static ref MESSAGES_CHECKED: IntCounter = register(IntCounter::new(
+        "r9k_messages_checked_total", "Messages checked for originality").unwrap());
+    static ref HASH_LOOKUPS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("r9k_hash_lookups_total", "Message hashes looked up in the dataset, by whether they were already there"),
+        &["result"]).unwrap());
+    static ref MUTES: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("r9k_mutes_total", "Stunlocks handed out, by kind"),
+        &["kind"]).unwrap());
+    static ref UNMUTES: IntCounter = register(IntCounter::new(
+        "r9k_unmutes_total", "Stunlocks that ran out").unwrap());
+    static ref QUERY_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("r9k_db_query_duration_seconds", "Time spent on database operations, by operation")
+            .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+        &["operation"]).unwrap());
+    static ref LOOP_DURATION: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("r9k_loop_duration_seconds", "Time taken by one pass of the unmute and streak decay loop")).unwrap());
+    static ref GATEWAY_EVENTS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("r9k_gateway_events_total", "Events received from the Discord gateway, by type"),
+        &["type"]).unwrap());
+    static ref HTTP_ERRORS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("r9k_discord_http_errors_total", "Failed requests to the Discord API, by what the bot was doing"),
+        &["operation"]).unwrap());
+}
+
+fn register<T: prometheus::core::Collector + Clone + 'static>(metric: T) -> T {
+    REGISTRY.register(Box::new(metric.clone())).expect("Could not register metric");
+    metric
+}
+
+pub fn message_checked() {
+    MESSAGES_CHECKED.inc();
+}
+
+pub fn hash_lookup(hit: bool) {
+    HASH_LOOKUPS.with_label_values(&[if hit { "hit" } else { "miss" }]).inc();
+}
+
+pub fn mute(kind: &str) {
+    MUTES.with_label_values(&[kind]).inc();
+}
+
+pub fn unmute() {
+    UNMUTES.inc();
+}
+
+// Observes how long a database operation took once the returned timer is dropped
+pub fn query_timer(operation: &str) -> HistogramTimer {
+    QUERY_DURATION.with_label_values(&[operation]).start_timer()
+}
+
+pub fn loop_timer() -> HistogramTimer {
+    LOOP_DURATION.start_timer()
+}
+
+pub fn gateway_event(kind: &str) {
+    GATEWAY_EVENTS.with_label_values(&[kind]).inc();
+}
+
+pub fn http_error(operation: &str) {
+    HTTP_ERRORS.with_label_values(&[operation]).inc();
+}
+
+// Serves `/metrics` in the Prometheus text format, meant to only be reachable by the scraper
+pub async fn serve(address: SocketAddr) {
+    // Metrics are registered on first use, touch them all so every one shows up from the first scrape
+    lazy_static::initialize(&MESSAGES_CHECKED);
+    lazy_static::initialize(&HASH_LOOKUPS);
+    lazy_static::initialize(&MUTES);
+    lazy_static::initialize(&UNMUTES);
+    lazy_static::initialize(&QUERY_DURATION);
+    lazy_static::initialize(&LOOP_DURATION);
+    lazy_static::initialize(&GATEWAY_EVENTS);
+    lazy_static::initialize(&HTTP_ERRORS);
+
+    let listener = match TcpListener::bind(address).await {
+        Ok(l) => l,
+        Err(why) => {
+            error!("Could not serve metrics on {}: {:?}", address, why);
+            return;
+        }
+    };
+    info!("Serving metrics on http://{}/metrics", address);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(respond(stream));
+            }
+            Err(why) => error!("Could not accept metrics connection: {:?}", why),
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream) {
+    // Only the request line matters, anything past the first read is ignored
+    let mut buffer = [0; 1024];
+    let read = match stream.read(&mut buffer).await {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut parts = request.split_whitespace();
+
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let mut body = Vec::new();
+            let encoder = TextEncoder::new();
+            encoder.encode(&REGISTRY.gather(), &mut body).expect("Could not encode metrics");
+
+            let mut response = format!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                encoder.format_type(), body.len()).into_bytes();
+            response.extend(body);
+            response
+        }
+        _ => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+    };
+
+    let _ = stream.write_all(&response).await;
+    let _ = stream.shutdown().await;
+}
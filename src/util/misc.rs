@@ -9,10 +9,13 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use fasthash::city;
 
+use super::{links, metrics};
+
 // Checks that a message was successfully sent; if not, then logs why to stdout.
 pub fn check_msg(result: SerenityResult<Message>) {
     if let Err(why) = result {
         println!("Error sending message: {:?}", why);
+        metrics::http_error("send_message");
     }
 }
 
@@ -20,14 +23,135 @@ pub fn to_string<T: Display>(a: T) -> String{
     format!("{}", a)
 }
 
+// Cuts text down to a number of characters, marking that something was cut off
+pub fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(limit.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
 pub fn hash(content: &str) -> u128{
+    hash_with(content, &Normalization::default())
+}
+
+pub fn hash_with(content: &str, options: &Normalization) -> u128 {
+    hash_normalized(&normalize(content, options))
+}
+
+// For text which has already been through normalize
+pub fn hash_normalized(normalized: &str) -> u128 {
+    city::hash128(normalized)
+}
+
+// Which Markdown constructs are kept apart from the prose instead of being run through the alphanumeric filter
+pub struct Normalization {
+    // Code blocks and inline code are kept verbatim, apart from whitespace
+    pub code_verbatim: bool,
+    // Spoiler markers are dropped, so `||text||` and `text` are the same
+    pub strip_spoilers: bool,
+    // URLs are kept whole in canonical form instead of being mangled along with the prose
+    pub separate_urls: bool,
+}
+
+impl Default for Normalization {
+    // Matches how messages were always hashed
+    fn default() -> Self {
+        Normalization {
+            code_verbatim: false,
+            strip_spoilers: true,
+            separate_urls: false,
+        }
+    }
+}
+
+// Prose is lowercased and reduced to alphanumerics, anything kept apart is appended as its own segment
+pub fn normalize(content: &str, options: &Normalization) -> String {
+    lazy_static! {
+        static ref CODE_BLOCK: Regex = Regex::new(r"(?s)```(?:[a-zA-Z0-9_+-]*\n)?(.*?)```").unwrap();
+        static ref INLINE_CODE: Regex = Regex::new(r"`([^`\n]+)`").unwrap();
+        static ref URL: Regex = Regex::new(r"https?://[^\s<>]+").unwrap();
+        static ref SPOILER: Regex = Regex::new(r"(?s)\|\|(.+?)\|\|").unwrap();
+    }
+    let mut rest = strip_quotes(content);
+    let mut segments: Vec<String> = Vec::new();
+
+    if options.code_verbatim {
+        rest = extract(&CODE_BLOCK, &rest, |c| segments.push(format!("code:{}", collapse_whitespace(c))));
+        rest = extract(&INLINE_CODE, &rest, |c| segments.push(format!("code:{}", collapse_whitespace(c))));
+    }
+    if options.separate_urls {
+        rest = extract(&URL, &rest, |u| segments.push(format!("url:{}", links::canonicalize(u).unwrap_or_else(|| u.to_lowercase()))));
+    }
+    if !options.strip_spoilers {
+        rest = extract(&SPOILER, &rest, |s| segments.push(format!("spoiler:{}", filter(s))));
+    }
+
+    let prose = filter(&rest);
+    if segments.is_empty() {
+        return prose;
+    }
+    format!("{}\u{0}{}", prose, segments.join("\u{0}"))
+}
+
+// Custom emoji and stickers as text of their own, for messages which have nothing else to hash
+// Empty if the message has neither
+pub fn emoji_stream(content: &str, stickers: &[u64]) -> String {
+    lazy_static! {
+        static ref CUSTOM_EMOJI: Regex = Regex::new(r"<a?:\w+:(\d+)>").unwrap();
+    }
+    // Keyed by id, so renaming an emoji doesn't make it original again
+    let mut stream: Vec<String> = CUSTOM_EMOJI.captures_iter(content)
+        .map(|c| format!("emoji{}", &c[1]))
+        .collect();
+    stream.extend(stickers.iter().map(|s| format!("sticker{}", s)));
+    stream.join(" ")
+}
+
+// Lowercases and drops everything but alphanumerics, along with anything enclosed by angle brackets
+fn filter(content: &str) -> String {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"(?m)<(.*?)>|[^a-zA-Z0-9]").unwrap();
     }
-    let content = content.to_lowercase();
-    let content = RE.replace_all(&content, "").to_string();
-    
-    city::hash128(content)
+    RE.replace_all(&content.to_lowercase(), "").to_string()
+}
+
+// Hands the innermost capture (or the whole match) of every match to `found` and removes it from the text
+fn extract<F: FnMut(&str)>(re: &Regex, content: &str, mut found: F) -> String {
+    for cap in re.captures_iter(content) {
+        let inner = cap.get(1).or_else(|| cap.get(0)).unwrap();
+        found(inner.as_str());
+    }
+    re.replace_all(content, " ").to_string()
+}
+
+fn collapse_whitespace(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Removes Markdown block quotes, so that quoting someone doesn't make a reply unoriginal
+// `>>> ` quotes everything after it, `> ` only its own line
+// A message that is nothing but a quote is left as is
+pub fn strip_quotes(content: &str) -> String {
+    let mut stripped = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with(">>> ") {
+            break;
+        }
+        if trimmed.starts_with("> ") || trimmed == ">" {
+            continue;
+        }
+        stripped.push(line);
+    }
+
+    let stripped = stripped.join("\n");
+    if stripped.trim().is_empty() {
+        return content.to_string();
+    }
+    stripped
 }
 
 pub fn seconds_to_string(secs: u64) -> String {
@@ -48,4 +172,98 @@ pub fn seconds_to_string(secs: u64) -> String {
     }
 
     format!("{}{}{}", hstr, mstr, sstr)
-}
\ No newline at end of file
+}
+
+// Parses durations such as `30m`, `1h30m` or `2d` into seconds, the inverse of seconds_to_string
+pub fn string_to_seconds(duration: &str) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut number = String::new();
+
+    for c in duration.to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let multiplier = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 60 * 60 * 24,
+            'w' => 60 * 60 * 24 * 7,
+            _ => return None,
+        };
+
+        let amount = number.parse::<u64>().ok()?;
+        total = total.checked_add(amount.checked_mul(multiplier)?)?;
+        number.clear();
+    }
+
+    // Either a trailing number without a unit or an empty string
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_normalization_matches_the_original_hash() {
+        // How every message was hashed before normalization became configurable
+        let original = |content: &str| Regex::new(r"(?m)<(.*?)>|[^a-zA-Z0-9]").unwrap().replace_all(&content.to_lowercase(), "").to_string();
+
+        for content in [
+            "Hey <@123>, look at <#456>!",
+            "Wow... that's GREAT?! 🎉🎉 <:pog:789>",
+            "||Spoilers|| for `code` and https://Example.com/a?b=c",
+            "Ünïcödé ✨ and 123 numbers",
+        ] {
+            let normalized = normalize(content, &Normalization::default());
+            assert_eq!(normalized, original(content));
+            assert_eq!(hash(content), city::hash128(original(content)));
+        }
+        assert_eq!(normalize("Hey <@123>, look: 🎉 ||Spoiler!||", &Normalization::default()), "heylookspoiler");
+    }
+
+    #[test]
+    fn durations_are_parsed() {
+        assert_eq!(string_to_seconds("30m"), Some(30 * 60));
+        assert_eq!(string_to_seconds("1h30m"), Some(90 * 60));
+        assert_eq!(string_to_seconds("1W2d3H4m5s"), Some(60 * 60 * 24 * 9 + 3 * 60 * 60 + 4 * 60 + 5));
+        assert_eq!(string_to_seconds("90"), None);
+        assert_eq!(string_to_seconds("1h30"), None);
+        assert_eq!(string_to_seconds("h"), None);
+        assert_eq!(string_to_seconds("0s"), None);
+        assert_eq!(string_to_seconds("5y"), None);
+        assert_eq!(string_to_seconds(""), None);
+    }
+
+    #[test]
+    fn overflowing_durations_are_rejected() {
+        assert_eq!(string_to_seconds("99999999999999999999s"), None);
+        assert_eq!(string_to_seconds("30000000000000000w"), None);
+        assert_eq!(string_to_seconds(&format!("{}s1s", u64::MAX)), None);
+    }
+
+    #[test]
+    fn quotes_are_stripped() {
+        assert_eq!(strip_quotes("> what they said\nmy reply"), "my reply");
+        assert_eq!(strip_quotes("my reply\n>>> everything\nafter this"), "my reply");
+        assert_eq!(strip_quotes("  > indented\n>\nreply"), "reply");
+        assert_eq!(strip_quotes(">not a quote"), ">not a quote");
+        assert_eq!(strip_quotes("> only a quote"), "> only a quote");
+        assert_eq!(strip_quotes(">>> only\na quote"), ">>> only\na quote");
+    }
+
+    #[test]
+    fn emoji_are_streamed_by_id() {
+        assert_eq!(emoji_stream("<:pog:123> and <a:dance:456>", &[]), "emoji123 emoji456");
+        assert_eq!(emoji_stream("<:renamed:123> and <a:dance:456>", &[]), emoji_stream("<:pog:123> and <a:dance:456>", &[]));
+        assert_eq!(emoji_stream("<:pog:123>", &[789]), "emoji123 sticker789");
+        assert_eq!(emoji_stream("🎉 :pog: <@123>", &[]), "");
+    }
+}
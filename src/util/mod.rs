@@ -1,4 +1,17 @@
 pub mod database;
 pub mod misc;
 pub mod embeds;
-pub mod check;
\ No newline at end of file
+pub mod check;
+pub mod history;
+pub mod stats;
+pub mod backfill;
+pub mod messages;
+pub mod reactions;
+pub mod links;
+pub mod locale;
+pub mod template;
+pub mod notices;
+pub mod guild_settings;
+pub mod dms;
+pub mod config;
+pub mod metrics;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use super::database::DatabaseTool;
+
+// Where the stunlock and warning notices for a guild are sent
+pub enum NoticeMode {
+    // Posted in the channel the violation happened in, deleted after the guild's lifetime
+    Channel,
+    // Only the log channel hears about it
+    Log,
+    // Sent privately to the offending user
+    Dm,
+}
+
+impl From<i64> for NoticeMode {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => NoticeMode::Log,
+            2 => NoticeMode::Dm,
+            _ => NoticeMode::Channel,
+        }
+    }
+}
+
+impl NoticeMode {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            NoticeMode::Channel => 0,
+            NoticeMode::Log => 1,
+            NoticeMode::Dm => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoticeMode::Channel => "channel",
+            NoticeMode::Log => "log",
+            NoticeMode::Dm => "dm",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "channel" => Some(NoticeMode::Channel),
+            "log" => Some(NoticeMode::Log),
+            "dm" | "dms" => Some(NoticeMode::Dm),
+            _ => None,
+        }
+    }
+}
+
+// The guild's notice mode, and how many seconds channel notices stay up for, 0 keeps them
+// Log mode falls back to the channel if the log channel has since been disabled
+pub async fn settings(database: &DatabaseTool, guild_id: u64) -> (NoticeMode, u64) {
+    let mode = match NoticeMode::from(database.retrieve_int("guild_settings", "notice_mode", "id", &guild_id).await) {
+        NoticeMode::Log if database.retrieve_int("guild_settings", "log_channel", "id", &guild_id).await == 0 => NoticeMode::Channel,
+        mode => mode,
+    };
+    let lifetime = database.retrieve_int("guild_settings", "notice_lifetime", "id", &guild_id).await as u64;
+    (mode, lifetime)
+}
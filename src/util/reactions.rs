@@ -0,0 +1,129 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{
+    database::{DatabaseTool, INTEGER, TEXT, scrub},
+    guild_settings::GuildSettings,
+    misc::to_string,
+    metrics,
+};
+
+// Reactions used in per-message mode are only remembered for this long
+const MESSAGE_RETENTION: u64 = 2592000;
+
+// Longest window a guild can keep an emoji from being reused in a channel for
+pub const MAX_REACTION_WINDOW: u64 = MESSAGE_RETENTION;
+
+pub enum ReactionMode {
+    // Reactions are never checked
+    Off,
+    // The same emoji can't be added to the same message twice
+    Message,
+    // An emoji can't be used again in the same channel within a window
+    Channel,
+}
+
+impl From<i64> for ReactionMode {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => ReactionMode::Message,
+            2 => ReactionMode::Channel,
+            _ => ReactionMode::Off,
+        }
+    }
+}
+
+impl ReactionMode {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            ReactionMode::Off => 0,
+            ReactionMode::Message => 1,
+            ReactionMode::Channel => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReactionMode::Off => "off",
+            ReactionMode::Message => "message",
+            ReactionMode::Channel => "channel",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(ReactionMode::Off),
+            "message" => Some(ReactionMode::Message),
+            "channel" => Some(ReactionMode::Channel),
+            _ => None,
+        }
+    }
+}
+
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("reactions").await {
+        database.create_table("reactions", &["guild", "channel", "message", "emoji", "user", "time"], &[INTEGER, INTEGER, INTEGER, TEXT, INTEGER, INTEGER]).await;
+    }
+}
+
+// Whether someone else already used the emoji, according to the guild's mode
+// Reactions by the same user don't count, so removing and re-adding a reaction isn't a repeat
+pub async fn is_repeat(database: &DatabaseTool, settings: &GuildSettings, channel_id: u64, message_id: u64, emoji: &str, user_id: u64) -> bool {
+    let _timer = metrics::query_timer("reactions_is_repeat");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let since = now.saturating_sub(settings.reaction_window);
+    let filter = match repeat_filter(settings.guild_id, channel_id, message_id, emoji, user_id, &settings.reaction_mode, since) {
+        Some(f) => f,
+        None => return false,
+    };
+
+    let row: (i64,) = sqlx::query_as(format!("SELECT COUNT(*) FROM reactions WHERE {}", filter).as_str())
+    .fetch_one(&database.pool)
+    .await
+    .expect("Could not query database [reactions is_repeat]");
+
+    row.0 > 0
+}
+
+// The recorded reactions which make a new one a repeat, None if nothing can
+// In channel mode only reactions since the start of the window count
+fn repeat_filter(guild_id: u64, channel_id: u64, message_id: u64, emoji: &str, user_id: u64, mode: &ReactionMode, since: u64) -> Option<String> {
+    let condition = match mode {
+        ReactionMode::Off => return None,
+        ReactionMode::Message => format!("message = {}", message_id),
+        ReactionMode::Channel => format!("channel = {} AND time >= {}", channel_id, since),
+    };
+
+    Some(format!("guild = {} AND emoji = '{}' AND user != {} AND {}", guild_id, scrub(emoji), user_id, condition))
+}
+
+// Also drops reactions which can no longer be repeated
+pub async fn record(database: &DatabaseTool, guild_id: u64, channel_id: u64, message_id: u64, emoji: &str, user_id: u64, window: u64) {
+    let _timer = metrics::query_timer("reactions_record");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    sqlx::query(format!("DELETE FROM reactions WHERE guild = {} AND time < {}", guild_id, now.saturating_sub(window.max(MESSAGE_RETENTION))).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [reactions record]");
+
+    database.insert_row("reactions", &[&to_string(guild_id), &to_string(channel_id), &to_string(message_id), emoji, &to_string(user_id), &to_string(now)]).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_user_readding_is_not_a_repeat() {
+        let message = repeat_filter(1, 2, 3, "👍", 4, &ReactionMode::Message, 700).unwrap();
+        assert_eq!(message, "guild = 1 AND emoji = '👍' AND user != 4 AND message = 3");
+
+        let channel = repeat_filter(1, 2, 3, "👍", 4, &ReactionMode::Channel, 700).unwrap();
+        assert_eq!(channel, "guild = 1 AND emoji = '👍' AND user != 4 AND channel = 2 AND time >= 700");
+    }
+
+    #[test]
+    fn off_never_repeats() {
+        assert!(repeat_filter(1, 2, 3, "👍", 4, &ReactionMode::Off, 700).is_none());
+    }
+}
@@ -0,0 +1,231 @@
+use sqlx::Row;
+
+use super::{
+    database::{DatabaseTool, INTEGER},
+    metrics,
+};
+
+pub enum Counter {
+    Checked,
+    Recorded,
+    Violations,
+    Mutes,
+    DmFailures,
+}
+
+impl Counter {
+    fn column(&self) -> &'static str {
+        match self {
+            Counter::Checked => "checked",
+            Counter::Recorded => "recorded",
+            Counter::Violations => "violations",
+            Counter::Mutes => "mutes",
+            Counter::DmFailures => "dm_failures",
+        }
+    }
+}
+
+pub enum UserCounter {
+    Original,
+    Violations,
+}
+
+impl UserCounter {
+    fn column(&self) -> &'static str {
+        match self {
+            UserCounter::Original => "original",
+            UserCounter::Violations => "violations",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UserStats {
+    pub user: u64,
+    pub original: u64,
+    pub violations: u64,
+}
+
+impl UserStats {
+    // Share of a user's messages in enforced channels that were original
+    pub fn ratio(&self) -> f64 {
+        let total = self.original + self.violations;
+        if total == 0 {
+            return 0.0;
+        }
+        self.original as f64 / total as f64
+    }
+}
+
+#[derive(Default)]
+pub struct ChannelStats {
+    pub channel: u64,
+    pub checked: u64,
+    pub recorded: u64,
+    pub violations: u64,
+    pub mutes: u64,
+    pub dm_failures: u64,
+}
+
+const COUNTERS: &[&str] = &["checked", "recorded", "violations", "mutes", "dm_failures"];
+const USER_COUNTERS: &[&str] = &["original", "violations"];
+
+// One row per guild and channel, counters only ever go up
+// Counters that can't be tied to a channel are kept under channel 0
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("stats").await {
+        database.create_table("stats",
+            &["guild", "channel", "checked", "recorded", "violations", "mutes", "dm_failures"],
+            &[INTEGER, INTEGER, INTEGER, INTEGER, INTEGER, INTEGER, INTEGER],
+        ).await;
+    } else if !database.column_exists("stats", "dm_failures").await {
+        database.add_column("stats", "dm_failures", INTEGER, Some("0")).await;
+    }
+
+    if !database.index_exists("stats", "guild_channel").await {
+        merge_duplicates(database, "stats", &["guild", "channel"], COUNTERS).await;
+        database.add_unique_key("stats", "guild_channel", &["guild", "channel"]).await;
+    }
+}
+
+pub async fn create_user_table(database: &DatabaseTool) {
+    if !database.table_exists("user_stats").await {
+        database.create_table("user_stats",
+            &["guild", "user", "original", "violations"],
+            &[INTEGER, INTEGER, INTEGER, INTEGER],
+        ).await;
+    }
+
+    if !database.index_exists("user_stats", "guild_user").await {
+        merge_duplicates(database, "user_stats", &["guild", "user"], USER_COUNTERS).await;
+        database.add_unique_key("user_stats", "guild_user", &["guild", "user"]).await;
+    }
+}
+
+// Older versions could race into several rows for the same key, sum them up so the key can be made unique
+async fn merge_duplicates(database: &DatabaseTool, table: &str, keys: &[&str], counters: &[&str]) {
+    let _timer = metrics::query_timer("stats_merge_duplicates");
+    let merged = format!("{}_merged", table);
+    let sums: Vec<String> = counters.iter().map(|c| format!("SUM({}) AS {}", c, c)).collect();
+    let columns = [keys, counters].concat().join(", ");
+
+    let queries = [
+        format!("DROP TABLE IF EXISTS {}", merged),
+        format!("CREATE TABLE {} AS SELECT {}, {} FROM {} GROUP BY {}", merged, keys.join(", "), sums.join(", "), table, keys.join(", ")),
+        format!("DELETE FROM {}", table),
+        format!("INSERT INTO {} ({}) SELECT {} FROM {}", table, columns, columns, merged),
+        format!("DROP TABLE {}", merged),
+    ];
+    for query in &queries {
+        sqlx::query(query.as_str())
+        .execute(&database.pool)
+        .await
+        .expect("Could not update database [stats merge_duplicates]");
+    }
+}
+
+// Creates the row the first time anything happens in a channel, in the same statement so concurrent messages can't both insert it
+pub async fn increment(database: &DatabaseTool, guild_id: u64, channel_id: u64, counter: Counter) {
+    let _timer = metrics::query_timer("stats_increment");
+    let column = counter.column();
+    let values: Vec<&str> = COUNTERS.iter()
+        .map(|c| if *c == column { "1" } else { "0" })
+        .collect();
+    sqlx::query(format!("INSERT INTO stats (guild, channel, {}) VALUES ({}, {}, {}) ON DUPLICATE KEY UPDATE {} = {} + 1",
+        COUNTERS.join(", "), guild_id, channel_id, values.join(", "), column, column).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [stats increment]");
+}
+
+pub async fn channels(database: &DatabaseTool, guild_id: u64) -> Vec<ChannelStats> {
+    let _timer = metrics::query_timer("stats_channels");
+    let result = sqlx::query(format!("SELECT channel, checked, recorded, violations, mutes, dm_failures FROM stats WHERE guild = {} ORDER BY checked DESC", guild_id).as_str())
+    .fetch_all(&database.pool)
+    .await
+    .expect("Could not query database [stats channels]");
+
+    result.iter()
+        .map(|r| ChannelStats {
+            channel: r.get::<i64, usize>(0) as u64,
+            checked: r.get::<i64, usize>(1) as u64,
+            recorded: r.get::<i64, usize>(2) as u64,
+            violations: r.get::<i64, usize>(3) as u64,
+            mutes: r.get::<i64, usize>(4) as u64,
+            dm_failures: r.get::<i64, usize>(5) as u64,
+        })
+        .collect()
+}
+
+// Users with the most stunlocks in a guild, along with how many they've had
+pub async fn top_offenders(database: &DatabaseTool, guild_id: u64, limit: u64) -> Vec<(u64, u64)> {
+    let _timer = metrics::query_timer("stats_top_offenders");
+    let result = sqlx::query(format!("SELECT user, COUNT(*) AS mutes FROM history WHERE guild = {} AND kind IN ('automatic', 'manual', 'reaction') AND duration > 0 GROUP BY user ORDER BY mutes DESC LIMIT {}", guild_id, limit).as_str())
+    .fetch_all(&database.pool)
+    .await
+    .expect("Could not query database [stats top_offenders]");
+
+    result.iter()
+        .map(|r| (r.get::<i64, usize>(0) as u64, r.get::<i64, usize>(1) as u64))
+        .collect()
+}
+
+pub async fn increment_user(database: &DatabaseTool, guild_id: u64, user_id: u64, counter: UserCounter) {
+    let _timer = metrics::query_timer("stats_increment_user");
+    let column = counter.column();
+    let values: Vec<&str> = USER_COUNTERS.iter()
+        .map(|c| if *c == column { "1" } else { "0" })
+        .collect();
+    sqlx::query(format!("INSERT INTO user_stats (guild, user, {}) VALUES ({}, {}, {}) ON DUPLICATE KEY UPDATE {} = {} + 1",
+        USER_COUNTERS.join(", "), guild_id, user_id, values.join(", "), column, column).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [stats increment_user]");
+}
+
+pub async fn user(database: &DatabaseTool, guild_id: u64, user_id: u64) -> UserStats {
+    let _timer = metrics::query_timer("stats_user");
+    let result = sqlx::query(format!("SELECT original, violations FROM user_stats WHERE guild = {} AND user = {}", guild_id, user_id).as_str())
+    .fetch_optional(&database.pool)
+    .await
+    .expect("Could not query database [stats user]");
+
+    match result {
+        Some(r) => UserStats {
+            user: user_id,
+            original: r.get::<i64, usize>(0) as u64,
+            violations: r.get::<i64, usize>(1) as u64,
+        },
+        None => UserStats {
+            user: user_id,
+            ..Default::default()
+        },
+    }
+}
+
+// Most original users first
+pub async fn leaderboard(database: &DatabaseTool, guild_id: u64, limit: u64) -> Vec<UserStats> {
+    let _timer = metrics::query_timer("stats_leaderboard");
+    let result = sqlx::query(format!("SELECT user, original, violations FROM user_stats WHERE guild = {} ORDER BY original DESC, violations ASC LIMIT {}", guild_id, limit).as_str())
+    .fetch_all(&database.pool)
+    .await
+    .expect("Could not query database [stats leaderboard]");
+
+    result.iter()
+        .map(|r| UserStats {
+            user: r.get::<i64, usize>(0) as u64,
+            original: r.get::<i64, usize>(1) as u64,
+            violations: r.get::<i64, usize>(2) as u64,
+        })
+        .collect()
+}
+
+pub async fn mute_count(database: &DatabaseTool, guild_id: u64, user_id: u64) -> u64 {
+    let _timer = metrics::query_timer("stats_mute_count");
+    let row: (i64,) = sqlx::query_as(format!("SELECT COUNT(*) FROM history WHERE guild = {} AND user = {} AND kind IN ('automatic', 'manual', 'reaction') AND duration > 0", guild_id, user_id).as_str())
+    .fetch_one(&database.pool)
+    .await
+    .expect("Could not query database [stats mute_count]");
+
+    row.0 as u64
+}
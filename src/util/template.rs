@@ -0,0 +1,267 @@
+use sqlx::Row;
+use serenity::utils::Color;
+
+use super::{
+    database::{DatabaseTool, INTEGER, TEXT, BOOL, scrub},
+    locale::tr,
+    metrics,
+};
+
+// Discord's limits for embed titles and descriptions
+pub const TITLE_LIMIT: usize = 256;
+pub const DESCRIPTION_LIMIT: usize = 4096;
+
+// Embeds guilds can customize, along with the placeholders each one provides
+#[derive(Clone, Copy)]
+pub enum TemplateKind {
+    Stunlock,
+    Unmute,
+}
+
+impl TemplateKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TemplateKind::Stunlock => "stunlock",
+            TemplateKind::Unmute => "unmute",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "stunlock" | "mute" => Some(TemplateKind::Stunlock),
+            "unmute" => Some(TemplateKind::Unmute),
+            _ => None,
+        }
+    }
+
+    pub fn placeholders(&self) -> &'static [&'static str] {
+        match self {
+            TemplateKind::Stunlock => &["user", "duration", "streak", "moderator", "reason", "channel"],
+            TemplateKind::Unmute => &["user", "guild"],
+        }
+    }
+}
+
+// A guild's overrides for an embed, empty fields keep the default
+pub struct Template {
+    pub title: String,
+    pub description: String,
+    pub color: Option<u32>,
+    pub shown: bool,
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template {
+            title: String::new(),
+            description: String::new(),
+            color: None,
+            shown: true,
+        }
+    }
+}
+
+pub enum TemplateError {
+    TooLong(usize),
+    Unclosed,
+    Unopened,
+    UnknownPlaceholder(String),
+}
+
+impl TemplateError {
+    pub fn describe(&self, locale: &str, kind: TemplateKind) -> String {
+        match self {
+            TemplateError::TooLong(limit) => tr(locale, "template-too-long", &[("limit", &*limit.to_string())]),
+            TemplateError::Unclosed => tr(locale, "template-unclosed", &[]),
+            TemplateError::Unopened => tr(locale, "template-unopened", &[]),
+            TemplateError::UnknownPlaceholder(name) => tr(locale, "template-unknown-placeholder", &[
+                ("placeholder", &*format!("`{{{}}}`", name)),
+                ("available", &*list(kind.placeholders())),
+            ]),
+        }
+    }
+}
+
+// Fills in `{name}` placeholders, `{{` and `}}` are literal braces
+// Templates are validated when they're set, so anything unexpected is left as is
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                match values.iter().find(|(n, _)| *n == name) {
+                    Some((_, v)) => rendered.push_str(v),
+                    None => rendered.push_str(&format!("{{{}}}", name)),
+                }
+            }
+            _ => rendered.push(c),
+        }
+    }
+
+    rendered
+}
+
+// Checks that every placeholder is one the embed provides and that braces are balanced
+pub fn validate(template: &str, allowed: &[&str], limit: usize) -> Result<(), TemplateError> {
+    if template.chars().count() > limit {
+        return Err(TemplateError::TooLong(limit));
+    }
+
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => { chars.next(); }
+            '}' if chars.peek() == Some(&'}') => { chars.next(); }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if !closed {
+                    return Err(TemplateError::Unclosed);
+                }
+                if !allowed.contains(&name.as_str()) {
+                    return Err(TemplateError::UnknownPlaceholder(name));
+                }
+            }
+            '}' => return Err(TemplateError::Unopened),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_title(kind: TemplateKind, title: &str) -> Result<(), TemplateError> {
+    validate(title, kind.placeholders(), TITLE_LIMIT)
+}
+
+pub fn validate_description(kind: TemplateKind, description: &str) -> Result<(), TemplateError> {
+    validate(description, kind.placeholders(), DESCRIPTION_LIMIT)
+}
+
+// Accepts `#rrggbb` or `rrggbb`
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+pub fn color(value: u32) -> Color {
+    Color::new(value)
+}
+
+pub fn list(placeholders: &[&str]) -> String {
+    placeholders.iter().map(|p| format!("`{{{}}}`", p)).collect::<Vec<String>>().join(", ")
+}
+
+// One row per guild and embed
+pub async fn create_table(database: &DatabaseTool) {
+    if !database.table_exists("templates").await {
+        database.create_table("templates",
+            &["guild", "kind", "title", "description", "color", "shown"],
+            &[INTEGER, TEXT, TEXT, TEXT, INTEGER, BOOL],
+        ).await;
+    }
+}
+
+pub async fn get(database: &DatabaseTool, guild_id: u64, kind: TemplateKind) -> Option<Template> {
+    let _timer = metrics::query_timer("template_get");
+    let row = sqlx::query(format!("SELECT title, description, color, shown FROM templates WHERE guild = {} AND kind = '{}'", guild_id, kind.name()).as_str())
+    .fetch_optional(&database.pool)
+    .await
+    .expect("Could not query database [template get]")?;
+
+    // A negative color means the default is kept
+    let color = row.get::<i64, usize>(2);
+    Some(Template {
+        title: row.get::<String, usize>(0),
+        description: row.get::<String, usize>(1),
+        color: if color < 0 { None } else { Some(color as u32) },
+        shown: row.get::<bool, usize>(3),
+    })
+}
+
+pub async fn set(database: &DatabaseTool, guild_id: u64, kind: TemplateKind, template: &Template) {
+    let _timer = metrics::query_timer("template_set");
+    reset(database, guild_id, kind).await;
+
+    let color = template.color.map_or(-1, |c| c as i64);
+    sqlx::query(format!("INSERT INTO templates VALUES ({}, '{}', '{}', '{}', {}, {})",
+        guild_id, kind.name(), scrub(&template.title), scrub(&template.description), color, template.shown as u8).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not insert into database [template set]");
+}
+
+pub async fn reset(database: &DatabaseTool, guild_id: u64, kind: TemplateKind) {
+    let _timer = metrics::query_timer("template_reset");
+    sqlx::query(format!("DELETE FROM templates WHERE guild = {} AND kind = '{}'", guild_id, kind.name()).as_str())
+    .execute(&database.pool)
+    .await
+    .expect("Could not update database [template reset]");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::misc::truncate;
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let values = [("user", "<@1>"), ("streak", "3")];
+        assert_eq!(render("{user} is on a streak of {streak}", &values), "<@1> is on a streak of 3");
+        assert_eq!(render("{{user}} stays {{literal}}", &values), "{user} stays {literal}");
+        assert_eq!(render("{guild} is unknown", &values), "{guild} is unknown");
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        let allowed = TemplateKind::Unmute.placeholders();
+        assert!(validate("{user} in {guild} {{ok}}", allowed, TITLE_LIMIT).is_ok());
+        assert!(matches!(validate(&"a".repeat(TITLE_LIMIT + 1), allowed, TITLE_LIMIT), Err(TemplateError::TooLong(TITLE_LIMIT))));
+        assert!(matches!(validate("{user", allowed, TITLE_LIMIT), Err(TemplateError::Unclosed)));
+        assert!(matches!(validate("user}", allowed, TITLE_LIMIT), Err(TemplateError::Unopened)));
+        assert!(matches!(validate("{streak}", allowed, TITLE_LIMIT), Err(TemplateError::UnknownPlaceholder(name)) if name == "streak"));
+    }
+
+    #[test]
+    fn colors_need_six_hex_digits() {
+        assert_eq!(parse_color("#ff8800"), Some(0xff8800));
+        assert_eq!(parse_color("ff8800"), Some(0xff8800));
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("#ff88001"), None);
+        assert_eq!(parse_color("#gg8800"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    #[test]
+    fn rendered_templates_fit_in_embeds() {
+        let reason = "a".repeat(DESCRIPTION_LIMIT);
+        let title = truncate(&render("{reason}", &[("reason", &reason)]), TITLE_LIMIT);
+        assert_eq!(title.chars().count(), TITLE_LIMIT);
+        assert!(title.ends_with('…'));
+
+        let description = truncate(&render("Muted: {reason}", &[("reason", &reason)]), DESCRIPTION_LIMIT);
+        assert_eq!(description.chars().count(), DESCRIPTION_LIMIT);
+        assert_eq!(truncate("short", TITLE_LIMIT), "short");
+    }
+}